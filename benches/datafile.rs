@@ -0,0 +1,145 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use olc_datafile_rust::{Datafile, DatafilePath, Reader};
+
+const ITERATIONS: usize = 100;
+const NODES: usize = 1_000;
+
+/// Counts the allocations made, to compare the memory usage of different approaches.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs a benchmark and prints the average time a single iteration took.
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    let mut total = Duration::ZERO;
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let result = f();
+        total += start.elapsed();
+        drop(result);
+    }
+
+    println!("{name:<24} {:>12?}", total / ITERATIONS as u32);
+}
+
+fn names() -> Vec<String> {
+    (0..NODES).map(|index| format!("node_{index}")).collect()
+}
+
+fn bench_lookup() {
+    let names = names();
+
+    bench("get (insert)", || {
+        let mut datafile = Datafile::new(None, None);
+
+        for name in &names {
+            datafile.get(name).set_integer(1, 0);
+        }
+
+        datafile
+    });
+
+    bench("get (insert, reserved)", || {
+        let mut datafile = Datafile::new(None, None);
+        datafile.reserve(names.len());
+
+        for name in &names {
+            datafile.get(name).set_integer(1, 0);
+        }
+
+        datafile
+    });
+
+    let mut datafile = Datafile::new(None, None);
+    for name in &names {
+        datafile.get(name);
+    }
+
+    bench("get (existing)", || {
+        names
+            .iter()
+            .map(|name| datafile.get(name).get_value_count())
+            .sum::<usize>()
+    });
+}
+
+fn bench_paths() {
+    let mut datafile = Datafile::new(None, None);
+    datafile
+        .get_path_mut("config.video.display.resolution")
+        .set_string_list(&["1920", "1080"]);
+
+    let dotted = "config.video.display.resolution[0]";
+    let path = DatafilePath::new(dotted);
+
+    bench("get_path", || {
+        (0..NODES).filter_map(|_| datafile.get_path(dotted)).count()
+    });
+
+    bench("DatafilePath::resolve", || {
+        (0..NODES).filter_map(|_| path.resolve(&datafile)).count()
+    });
+}
+
+/// Prints the number of allocations and bytes allocated while running the closure once.
+fn count_allocations<T>(name: &str, f: impl FnOnce() -> T) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let result = f();
+
+    println!(
+        "{name:<24} {:>12} allocations, {:>10} bytes",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    );
+    drop(result);
+}
+
+fn bench_interning() {
+    let mut text = String::new();
+    for index in 0..NODES {
+        text += &format!("entity_{index}\n{{\n\tx = 1\n\ty = 2\n\tz = 3\n}}\n");
+    }
+
+    for intern_keys in [false, true] {
+        let name = if intern_keys {
+            "read (interned keys)"
+        } else {
+            "read"
+        };
+
+        count_allocations(name, || {
+            let mut datafile = Datafile::new(None, None);
+            let mut reader = Reader::new(&mut datafile);
+            reader.options.intern_keys = intern_keys;
+            reader.read_bytes(text.as_bytes()).unwrap();
+            datafile
+        });
+    }
+}
+
+fn main() {
+    bench_lookup();
+    bench_paths();
+    bench_interning();
+}
@@ -1,6 +1,9 @@
 use olc_datafile_rust::Datafile;
 
 fn main() {
+    let path = std::env::temp_dir().join("datafile.txt");
+    let copy_path = std::env::temp_dir().join("datafile2.txt");
+
     let mut datafile = Datafile::new(None, None);
 
     let some_node = datafile.get("some_node");
@@ -17,17 +20,13 @@ fn main() {
     pc.get("processor").set_string("intel", 0);
     pc.get("ram").set_integer(32, 0);
 
-    datafile
-        .write("./datafile.txt")
-        .expect("Failed to write datafile");
+    datafile.write(&path).expect("Failed to write datafile");
 
     let mut datafile = Datafile::new(None, None);
-    datafile
-        .read("./datafile.txt")
-        .expect("Failed to read datafile");
+    datafile.read(&path).expect("Failed to read datafile");
 
     datafile
-        .write("./datafile2.txt")
+        .write(&copy_path)
         .expect("Failed to write datafile");
 
     println!("{:?}", datafile.get("some_node"));
@@ -0,0 +1,175 @@
+use crate::datafile::Datafile;
+use crate::error::DatafileError;
+
+/// A builder for configuring a datafile before it is used. Any option that isn't set keeps the
+/// default of `Datafile::new`.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::DatafileBuilder;
+/// let datafile = DatafileBuilder::new()
+///     .list_separator(';')
+///     .indent_spaces(4)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(datafile.list_separator, ';');
+/// assert_eq!(datafile.whitespace_sequence, "    ");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DatafileBuilder {
+    list_separator: Option<char>,
+    quote_char: Option<char>,
+    whitespace_sequence: Option<String>,
+    case_insensitive: bool,
+}
+
+impl DatafileBuilder {
+    /// Creates a builder with all options unset.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the character used for separating list values.
+    #[must_use]
+    pub const fn list_separator(mut self, list_separator: char) -> Self {
+        self.list_separator = Some(list_separator);
+        self
+    }
+
+    /// Sets the character used for delimiting values that contain reserved characters. It may
+    /// neither be whitespace, nor a character with a meaning of its own, which is checked when
+    /// building.
+    #[must_use]
+    pub const fn quote_char(mut self, quote_char: char) -> Self {
+        self.quote_char = Some(quote_char);
+        self
+    }
+
+    /// Sets the character sequence used for indentation. It may only consist of whitespace,
+    /// which is checked when building.
+    #[must_use]
+    pub fn whitespace_sequence(mut self, whitespace_sequence: &str) -> Self {
+        self.whitespace_sequence = Some(whitespace_sequence.to_string());
+        self
+    }
+
+    /// Indents every level with the given number of spaces.
+    #[must_use]
+    pub fn indent_spaces(self, count: usize) -> Self {
+        self.whitespace_sequence(&" ".repeat(count))
+    }
+
+    /// Indents every level with a single tab.
+    #[must_use]
+    pub fn indent_tabs(self) -> Self {
+        self.whitespace_sequence("\t")
+    }
+
+    /// Enables or disables case-insensitive lookups, see `Datafile::set_case_insensitive`.
+    #[must_use]
+    pub const fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Creates the configured datafile.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the whitespace sequence contains characters other
+    /// than whitespace, or if the quote character is reserved, as the file couldn't be parsed
+    /// back otherwise.
+    pub fn build(self) -> Result<Datafile, DatafileError> {
+        if let Some(sequence) = &self.whitespace_sequence {
+            if !sequence.chars().all(char::is_whitespace) {
+                return Err(DatafileError::InvalidWhitespaceSequence(sequence.clone()));
+            }
+        }
+
+        let mut datafile = Datafile::new(self.list_separator, self.whitespace_sequence.as_deref());
+
+        if let Some(quote_char) = self.quote_char {
+            if quote_char.is_whitespace()
+                || ['=', '{', '}', '#', datafile.list_separator].contains(&quote_char)
+            {
+                return Err(DatafileError::InvalidQuoteChar(quote_char));
+            }
+
+            datafile.quote_char = quote_char;
+        }
+
+        datafile.set_case_insensitive(self.case_insensitive);
+
+        Ok(datafile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::reader::Reader;
+    use crate::processor::writer::Writer;
+
+    #[test]
+    fn test_builder_defaults() {
+        let datafile = DatafileBuilder::new().build().unwrap();
+        assert_eq!(datafile.list_separator, ',');
+        assert_eq!(datafile.whitespace_sequence, "\t");
+        assert_eq!(datafile.quote_char, '"');
+        assert!(!datafile.is_case_insensitive());
+    }
+
+    #[test]
+    fn test_builder_indentation() {
+        assert_eq!(
+            DatafileBuilder::new()
+                .indent_tabs()
+                .build()
+                .unwrap()
+                .whitespace_sequence,
+            "\t"
+        );
+
+        assert_eq!(
+            DatafileBuilder::new()
+                .whitespace_sequence("a")
+                .build()
+                .unwrap_err(),
+            DatafileError::InvalidWhitespaceSequence("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_two_space_indent() {
+        let mut datafile = DatafileBuilder::new().indent_spaces(2).build().unwrap();
+        datafile.get("a").get("b").get("c").set_integer(1, 0);
+
+        let text = Writer::new(&datafile).write_to_string();
+        assert_eq!(text, "a\n{\n\n  b\n  {\n    c = 1\n  }\n}\n");
+
+        let mut result = DatafileBuilder::new().indent_spaces(2).build().unwrap();
+        Reader::new(&mut result).read_str(&text).unwrap();
+        assert_eq!(result.get_property("a.b.c").get_integer(0), 1);
+        assert_eq!(Writer::new(&result).write_to_string(), text);
+    }
+
+    #[test]
+    fn test_builder_quote_char() {
+        let mut datafile = DatafileBuilder::new().quote_char('\'').build().unwrap();
+        assert_eq!(datafile.quote_char, '\'');
+        assert_eq!(datafile.get("a").get("b").quote_char, '\'');
+
+        for quote_char in [' ', '=', '{', '#', ','] {
+            assert_eq!(
+                DatafileBuilder::new()
+                    .quote_char(quote_char)
+                    .build()
+                    .unwrap_err(),
+                DatafileError::InvalidQuoteChar(quote_char)
+            );
+        }
+    }
+}
@@ -1,8 +1,16 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
 
-use crate::lexical::Serializable;
-use crate::processor::reader::Reader;
-use crate::processor::writer::Writer;
+use crate::diff::{diff_nodes, Change};
+use crate::error::DatafileError;
+use crate::interner::Interner;
+use crate::lexical::{parse_bool, parse_real, to_hex, Deserialize, Serialize};
+use crate::processor::reader::{parse_raw_values, ReadOptions, Reader};
+use crate::processor::writer::{Truncation, WriteOptions, Writer};
 
 /// A datafile is a structured file format that is used to store data. In the words of it's inventor,
 /// it is "great for serializing and deserializing data, i.e. Saving Things!".
@@ -16,6 +24,10 @@ use crate::processor::writer::Writer;
 /// Accessing values or child nodes is done through the `get` method. Should the node not exist,
 /// it will be created.
 ///
+/// Sibling nodes may share a name, for example when a file contains the same block twice. In that
+/// case `get` and `has_property` always refer to the first of them, while `get_all` returns
+/// every one of them in the order they were added.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -35,32 +47,271 @@ use crate::processor::writer::Writer;
 /// let mut datafile = Datafile::new(None, None);
 /// datafile.read("test.txt").unwrap();
 /// ```
-#[derive(Debug, Clone)]
+///
+/// Cloning a datafile clones the whole tree below it, which can be expensive for big trees. Use
+/// `clone_shallow` if only the node itself is needed.
+#[derive(Clone)]
 pub struct Datafile {
-    /// The character to use for separating list values. Defaults to `,`.
+    /// The character to use for separating list values. Defaults to `,`. Child nodes inherit the
+    /// separator of their parent when they are created, and can be changed individually
+    /// afterwards. The writer always uses the separator of the node being written.
     pub list_separator: char,
+    /// The character used for delimiting values and keys that contain reserved characters, e.g.
+    /// the list separator. Defaults to `"`. Like the list separator, child nodes inherit it when
+    /// they are created.
+    pub quote_char: char,
+    /// The number of values `try_set_string` allows a node to grow to. Defaults to 65536. Child
+    /// nodes inherit it when they are created.
+    pub max_value_count: usize,
     /// The character sequence to use for indentation. Defaults to `\t`.
     pub whitespace_sequence: String,
 
+    pub(crate) case_insensitive: bool,
     pub(crate) is_comment: bool,
+    pub(crate) doc_comment: Option<String>,
+    pub(crate) source_line: Option<usize>,
     pub(crate) contents: Vec<String>,
-    pub(crate) object_vec: Vec<(String, Datafile)>,
-    pub(crate) object_map: HashMap<String, usize>,
+    /// The indices of the values that are null, in ascending order. Their content is empty.
+    pub(crate) nulls: Vec<usize>,
+    /// The indices of the values set as reals, in ascending order, which the writer rounds to
+    /// `WriteOptions::real_precision`.
+    pub(crate) reals: Vec<usize>,
+    pub(crate) object_vec: Vec<(Arc<str>, Datafile)>,
+    pub(crate) object_map: HashMap<Arc<str>, usize>,
+}
+
+/// An item of a datafile, as returned by `Datafile::entries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry<'a> {
+    /// A standalone comment, including the leading `#`.
+    Comment(&'a str),
+    /// A node without children, written as a single line, e.g. `name = Javid`.
+    Value { key: &'a str, values: &'a [String] },
+    /// A node with children, written as a block.
+    Node(&'a str, &'a Datafile),
+}
+
+/// An iterator over the child nodes of a datafile and their names, created by iterating over a
+/// borrowed datafile. Comments are skipped.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    children: std::slice::Iter<'a, (Arc<str>, Datafile)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a Datafile);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.children
+            .find(|(_, node)| !node.is_comment)
+            .map(|(name, node)| (&**name, node))
+    }
+}
+
+/// An iterator moving the child nodes out of a datafile, along with their names, created by
+/// iterating over an owned datafile. Comments are skipped.
+#[derive(Debug)]
+pub struct IntoIter {
+    children: std::vec::IntoIter<(Arc<str>, Datafile)>,
+}
+
+impl Iterator for IntoIter {
+    type Item = (String, Datafile);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.children
+            .find(|(_, node)| !node.is_comment)
+            .map(|(name, node)| (name.to_string(), node))
+    }
+}
+
+/// A path in the notation of `Datafile::get_path`, parsed once so it can be resolved repeatedly,
+/// e.g. for config values that are read every frame, without splitting the string each time.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::{Datafile, DatafilePath};
+/// let path = DatafilePath::new("pc.ram");
+///
+/// let mut desktop = Datafile::new(None, None);
+/// desktop.get("pc").get("ram").set_integer(32, 0);
+/// let laptop = Datafile::new(None, None);
+///
+/// assert_eq!(path.resolve(&desktop).map(|ram| ram.get_integer(0)), Some(32));
+/// assert!(path.resolve(&laptop).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DatafilePath {
+    segments: Vec<(String, usize)>,
+}
+
+impl DatafilePath {
+    /// Parses a path, using the same notation as `Datafile::get_path`.
+    #[must_use]
+    pub fn new(path: &str) -> Self {
+        let segments = path
+            .split('.')
+            .map(|segment| {
+                let (name, index) = Datafile::parse_path_segment(segment);
+                (name.to_string(), index)
+            })
+            .collect();
+
+        Self { segments }
+    }
+
+    /// Returns the node at this path below the given datafile, like `Datafile::get_path` does.
+    #[must_use]
+    pub fn resolve<'a>(&self, datafile: &'a Datafile) -> Option<&'a Datafile> {
+        self.segments
+            .iter()
+            .try_fold(datafile, |node, (name, index)| node.get_nth(name, *index))
+    }
+}
+
+/// Iterates over the child nodes of the datafile in the order they appear in, skipping comments.
+/// Sibling nodes sharing a name are all included.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::Datafile;
+/// let mut datafile = Datafile::new(None, None);
+/// datafile.get("a").set_integer(1, 0);
+/// datafile.get("b").set_integer(2, 0);
+///
+/// for (name, node) in datafile {
+///     println!("{name} = {}", node.get_integer(0));
+/// }
+/// ```
+impl IntoIterator for Datafile {
+    type Item = (String, Self);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            children: self.object_vec.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Datafile {
+    type Item = (&'a str, &'a Datafile);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            children: self.object_vec.iter(),
+        }
+    }
+}
+
+/// Two datafiles are equal if they hold the same values, comments and child nodes in the same
+/// order. Their configuration, e.g. the list separator, recorded source lines and which values
+/// were set as reals are ignored.
+impl PartialEq for Datafile {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_comment == other.is_comment
+            && self.doc_comment == other.doc_comment
+            && self.contents == other.contents
+            && self.nulls == other.nulls
+            && self.object_vec == other.object_vec
+    }
+}
+
+impl Eq for Datafile {}
+
+/// Hashes the same parts of a datafile that are compared for equality, so equal datafiles hash
+/// equally, e.g. to use them as keys for caching.
+impl Hash for Datafile {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.is_comment.hash(state);
+        self.doc_comment.hash(state);
+        self.contents.hash(state);
+        self.nulls.hash(state);
+        self.object_vec.hash(state);
+    }
+}
+
+/// Formats the datafile in its text format, as `write` would write it. The values of the datafile
+/// itself, if any, are written on the first line after an equal sign. Deep or wide trees are
+/// truncated, with the omitted parts replaced by the number of nodes they contain. The alternate
+/// form, `{:#?}`, writes the whole tree.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::Datafile;
+/// let mut datafile = Datafile::new(None, None);
+/// datafile.get("pc").get("ram").set_integer(32, 0);
+///
+/// assert_eq!(format!("{datafile:?}"), "pc\n{\n\tram = 32\n}\n");
+/// ```
+impl fmt::Debug for Datafile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let truncation = (!f.alternate()).then_some(Truncation {
+            max_depth: DEBUG_MAX_DEPTH,
+            max_entries: DEBUG_MAX_ENTRIES,
+        });
+
+        f.write_str(&Writer::new(self).write_to_debug_string(truncation))
+    }
 }
 
 const DEFAULT_LIST_SEPARATOR: char = ',';
+const DEFAULT_QUOTE_CHAR: char = '"';
+const DEFAULT_MAX_VALUE_COUNT: usize = 1 << 16;
 const DEFAULT_WHITESPACE_SEQUENCE: &str = "\t";
+const DEBUG_MAX_DEPTH: usize = 8;
+const DEBUG_MAX_ENTRIES: usize = 32;
 
 impl Default for Datafile {
     fn default() -> Self {
         Self {
             list_separator: DEFAULT_LIST_SEPARATOR,
+            quote_char: DEFAULT_QUOTE_CHAR,
+            max_value_count: DEFAULT_MAX_VALUE_COUNT,
             whitespace_sequence: DEFAULT_WHITESPACE_SEQUENCE.to_string(),
             contents: vec![],
+            nulls: vec![],
+            reals: vec![],
             object_vec: vec![],
             object_map: HashMap::new(),
+            case_insensitive: false,
             is_comment: false,
+            doc_comment: None,
+            source_line: None,
+        }
+    }
+}
+
+/// Creates a datafile with a child node holding a single value for every entry of the map. As a
+/// map has no order, the nodes are sorted by name, so the result is reproducible.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use olc_datafile_rust::Datafile;
+/// let map = HashMap::from([("name".to_string(), "Javid".to_string())]);
+/// let mut datafile = Datafile::from(map);
+///
+/// assert_eq!(datafile.get("name").get_string(0), "Javid");
+/// ```
+impl From<HashMap<String, String>> for Datafile {
+    fn from(map: HashMap<String, String>) -> Self {
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort_unstable();
+
+        let mut datafile = Self::default();
+        datafile.reserve(entries.len());
+
+        for (name, value) in entries {
+            datafile.get(&name).contents.push(value);
         }
+
+        datafile
     }
 }
 
@@ -78,6 +329,169 @@ impl Datafile {
         }
     }
 
+    /// Clones the node's configuration and values, but none of its children. This is a lot
+    /// cheaper than `clone` for nodes with big trees below them.
+    #[must_use]
+    pub fn clone_shallow(&self) -> Self {
+        Self {
+            list_separator: self.list_separator,
+            quote_char: self.quote_char,
+            max_value_count: self.max_value_count,
+            whitespace_sequence: self.whitespace_sequence.clone(),
+            case_insensitive: self.case_insensitive,
+            is_comment: self.is_comment,
+            doc_comment: self.doc_comment.clone(),
+            source_line: self.source_line,
+            contents: self.contents.clone(),
+            nulls: self.nulls.clone(),
+            reals: self.reals.clone(),
+            object_vec: vec![],
+            object_map: HashMap::new(),
+        }
+    }
+
+    /// Enables or disables case-insensitive lookups of child nodes for this node and all of its
+    /// descendants. Nodes added afterwards inherit the setting. Only ASCII case is ignored, and
+    /// the original casing of every name is kept when writing.
+    ///
+    /// Should multiple siblings only differ in case, e.g. `Name` and `name`, lookups resolve to
+    /// whichever of them was added first, just like with siblings sharing the exact same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_case_insensitive(true);
+    ///
+    /// datafile.get("Name").set_string("Javid", 0);
+    /// assert!(datafile.has_property("name"));
+    /// assert_eq!(datafile.get("NAME").get_string(0), "Javid");
+    /// ```
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+
+        for (_, node) in &mut self.object_vec {
+            node.set_case_insensitive(case_insensitive);
+        }
+
+        self.rebuild_object_map();
+    }
+
+    /// Returns whether lookups of child nodes ignore ASCII case.
+    #[inline]
+    #[must_use]
+    pub const fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Attaches a comment to this node, which the writer puts on the line(s) right before it. Each
+    /// line of the text becomes a line of its own, written directly after a `#`, so a leading
+    /// space gives the usual `# text`. An empty text removes the comment.
+    ///
+    /// When reading, comment lines directly preceding a node, without an empty line in between,
+    /// are attached to it this way, with everything after their `#` kept as is. Other comments
+    /// are kept as standalone comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read_bytes(b"# In gigabytes\nram = 32\n").unwrap();
+    ///
+    /// assert_eq!(datafile.get("ram").doc_comment(), Some(" In gigabytes"));
+    /// ```
+    pub fn set_doc_comment(&mut self, text: &str) {
+        self.doc_comment = (!text.is_empty()).then(|| text.to_string());
+    }
+
+    /// Returns the comment attached to this node, see `set_doc_comment`.
+    #[inline]
+    #[must_use]
+    pub fn doc_comment(&self) -> Option<&str> {
+        self.doc_comment.as_deref()
+    }
+
+    /// Reserves capacity for at least `additional` more child nodes, which avoids repeated
+    /// reallocations when inserting many nodes at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.reserve(1_000);
+    ///
+    /// for index in 0..1_000 {
+    ///     datafile.get(&format!("node_{index}")).set_integer(index, 0);
+    /// }
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.object_vec.reserve(additional);
+        self.object_map.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the values and child nodes of this node and all of its children
+    /// as much as possible. Useful to reclaim memory after many nodes were cleared or removed,
+    /// e.g. before keeping a datafile loaded for a long time.
+    pub fn shrink_to_fit(&mut self) {
+        for value in &mut self.contents {
+            value.shrink_to_fit();
+        }
+
+        self.contents.shrink_to_fit();
+        self.nulls.shrink_to_fit();
+        self.reals.shrink_to_fit();
+        self.object_vec.shrink_to_fit();
+        self.object_map.shrink_to_fit();
+
+        for (_, child) in &mut self.object_vec {
+            child.shrink_to_fit();
+        }
+    }
+
+    /// Removes all values and child nodes, while keeping the configuration such as the list
+    /// separator and whitespace sequence. Useful when reusing a datafile, e.g. to read another
+    /// file into it.
+    pub fn clear(&mut self) {
+        self.contents.clear();
+        self.nulls.clear();
+        self.reals.clear();
+        self.object_vec.clear();
+        self.object_map.clear();
+    }
+
+    /// Recursively removes child nodes that hold neither values nor children, e.g. ones that were
+    /// only created by a stray `get`. Nodes whose children are all removed are removed as well.
+    /// Comments are removed too, unless `keep_comments` is set, but don't keep a node from being
+    /// removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("player").get("name").set_string("Javid", 0);
+    /// datafile.get("player").get("inventory").get("sword");
+    ///
+    /// datafile.normalize(false);
+    /// assert!(datafile.get("player").has_property("name"));
+    /// assert!(!datafile.get("player").has_property("inventory"));
+    /// ```
+    pub fn normalize(&mut self, keep_comments: bool) {
+        self.object_vec.retain_mut(|(_, node)| {
+            if node.is_comment {
+                return keep_comments;
+            }
+
+            node.normalize(keep_comments);
+            !node.contents.is_empty() || node.child_count() > 0
+        });
+
+        self.rebuild_object_map();
+    }
+
     /// Writes a datafile to disk.
     ///
     /// # Examples
@@ -94,214 +508,3389 @@ impl Datafile {
     /// # Errors
     ///
     /// This function will return an error if the file cannot be written to.
-    pub fn write(&self, path: &str) -> std::io::Result<()> {
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
         let mut writer = Writer::new(self);
         writer.write(path)
     }
 
-    /// Reads a datafile from disk, into the current datafile.
+    /// Writes a datafile to disk like `write`, but formatted with the given options.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// # use olc_datafile_rust::Datafile;
-    /// let mut datafile = Datafile::new(None, None);
-    /// datafile.read("test.txt").unwrap();
+    /// # use olc_datafile_rust::{Datafile, WriteOptions};
+    /// let datafile = Datafile::new(None, None);
     ///
-    /// // The Datafile is now populated with the contents of test.txt
+    /// let options = WriteOptions {
+    ///     sort_keys: true,
+    ///     crlf: true,
+    ///     ..WriteOptions::default()
+    /// };
+    /// datafile.write_with("test.txt", &options).unwrap();
     /// ```
     ///
     /// # Errors
     ///
-    /// This function will return an error if the file cannot be read from, or is otherwise
-    /// corrupted.
-    pub fn read(&mut self, path: &str) -> std::io::Result<()> {
-        let reader = Reader::new(self);
-        reader.read(path)
-    }
-
-    /// Sets a string value to the given index. Note that if the index is higher than the current
-    /// length of the list, the list will be extended with empty string values.
-    pub fn set_string(&mut self, value: &str, index: usize) {
-        if index >= self.contents.len() {
-            self.contents.resize(index + 1, String::new());
-        }
-
-        self.contents[index] = value.to_string();
+    /// This function will return an error if the file cannot be written to.
+    pub fn write_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &WriteOptions,
+    ) -> std::io::Result<()> {
+        let mut writer = Writer::with_options(self, options.clone());
+        writer.write(path)
     }
 
-    /// Gets a string value from the given index. If the index is out of bounds, an empty string
-    /// will be returned.
-    #[inline]
+    /// Serializes the datafile into a string formatted with the given options, exactly as
+    /// `write_with` would write it to disk.
     #[must_use]
-    pub fn get_string(&self, index: usize) -> String {
-        self.contents.get(index).unwrap_or(&String::new()).clone()
-    }
-
-    /// Appends a real (numeric) value to the datafile.
-    #[inline]
-    pub fn set_real(&mut self, value: f32, index: usize) {
-        self.set_string(&value.serialize(), index);
+    pub fn write_to_string_with(&self, options: &WriteOptions) -> String {
+        let mut writer = Writer::with_options(self, options.clone());
+        writer.write_to_string()
     }
 
-    /// Gets a real (numeric) value from the given index. If the index is out of bounds, or the
-    /// value cannot be parsed as a real, 0.0 will be returned.
-    #[inline]
-    #[must_use]
-    pub fn get_real(&self, index: usize) -> f32 {
-        f32::deserialize(&self.get_string(index))
+    /// Writes the datafile to the given output, e.g. a socket, formatted with the given options.
+    /// The output is written in chunks while the tree is traversed, instead of being collected in
+    /// memory first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the output cannot be written to.
+    pub fn write_into<W: std::io::Write>(
+        &self,
+        output: W,
+        options: &WriteOptions,
+    ) -> std::io::Result<()> {
+        let mut writer = Writer::with_options(self, options.clone());
+        writer.write_into(output)
     }
 
-    /// Sets an integer value to the datafile.
-    #[inline]
-    pub fn set_integer(&mut self, value: i32, index: usize) {
-        self.set_string(&value.serialize(), index);
+    /// Writes only the child node with the given name to disk, with the node itself as the root
+    /// block, so reading the file yields a datafile holding just that node under its name. This
+    /// is useful for saving parts of a big datafile incrementally.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("player").get("health").set_integer(100, 0);
+    /// datafile.get("world").get("seed").set_integer(42, 0);
+    ///
+    /// datafile.write_subtree("player", "player.txt").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no child node with the given name, or if
+    /// the file cannot be written to.
+    pub fn write_subtree<P: AsRef<Path>>(&self, name: &str, path: P) -> std::io::Result<()> {
+        let mut writer = Writer::new(self);
+        writer.write_subtree(name, path)
     }
 
-    /// Gets an integer value from the given index. If the index is out of bounds, or the value
-    /// cannot be parsed as an integer, 0 will be returned. Real values will be truncated, not
-    /// rounded.
-    #[inline]
+    /// Serializes only the child node with the given name into a string, exactly as
+    /// `write_subtree` would write it to disk. Returns `None` if there is no such node.
     #[must_use]
-    pub fn get_integer(&self, index: usize) -> i32 {
-        i32::deserialize(&self.get_string(index))
+    pub fn write_subtree_to_string(&self, name: &str) -> Option<String> {
+        let mut writer = Writer::new(self);
+        writer.subtree_to_string(name)
     }
 
-    /// Returns the number of items in the datafile. Does not include child node's contents.
-    #[inline]
+    /// Returns the values of this node joined by its list separator, with the same quoting `write`
+    /// applies, i.e. exactly what is written after the equal sign of the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["c++", "vhdl, verilog", ""]);
+    ///
+    /// assert_eq!(datafile.value_string(), r#"c++, "vhdl, verilog", """#);
+    /// ```
     #[must_use]
-    pub fn get_value_count(&self) -> usize {
-        self.contents.len()
+    pub fn value_string(&self) -> String {
+        self.value_string_with(&WriteOptions::new())
     }
 
-    /// Returns a child node with the given name. If the node does not exist, it will be created.
-    /// This can be chained to create a path of nodes. For example, `datafile.get("a").get("b")`
-    /// will either return the node `b` under `a`, or create it if it does not exist.
-    pub fn get(&mut self, name: &str) -> &mut Self {
-        if !self.object_map.contains_key(name) {
-            self.object_map
-                .insert(name.to_string(), self.object_vec.len());
-
-            self.push_object(
-                name,
-                Self::new(Some(self.list_separator), Some(&self.whitespace_sequence)),
-            );
-        }
-
-        &mut self.object_vec[self.object_map[name]].1
+    /// Does the same as `value_string`, but formats the values with the given options, like
+    /// `write_with` would.
+    #[must_use]
+    pub fn value_string_with(&self, options: &WriteOptions) -> String {
+        Writer::value_string(self, options)
     }
 
-    /// Checks if a child node or value with the given name exists.
-    #[inline]
-    #[must_use]
-    pub fn has_property(&self, name: &str) -> bool {
-        self.object_map.contains_key(name)
+    /// Writes a datafile to disk like `write`, but through a temporary file that replaces the
+    /// destination once it has been written completely. A crash or error while writing never
+    /// corrupts an existing file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written to.
+    pub fn write_atomic<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = Writer::new(self);
+        writer.write_atomic(path)
     }
 
-    /// Returns the datafile at a given path using dot notation. If no node exists at the given
-    /// path, they will get inserted.
+    /// Appends the datafile's values and nodes to the end of a file on disk, without rewriting
+    /// what's already in there. The file is created if it doesn't exist. The caller is
+    /// responsible for the combined file to be structurally correct, e.g. not to append in the
+    /// middle of an unclosed node.
     ///
     /// # Examples
+    ///
     /// ```no_run
     /// # use olc_datafile_rust::Datafile;
-    /// let mut datafile = Datafile::new(None, None);
-    /// datafile.read("test.txt").unwrap();
+    /// let mut entry = Datafile::new(None, None);
+    /// entry.get("entry").get("message").set_string("started", 0);
     ///
-    /// let node = datafile.get_property("a.b.c");
+    /// entry.append("log.txt").unwrap();
     /// ```
-    pub fn get_property(&mut self, name: &str) -> &Self {
-        let Some(dot_index) = name.find('.') else {
-            return self.get(name);
-        };
-
-        let (node_name, rest) = name.split_at(dot_index);
-
-        if self.has_property(node_name) {
-            self.get(node_name).get_property(&rest[1..])
-        } else {
-            self.get(node_name)
-        }
-    }
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written to.
+    pub fn append<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = Writer::new(self);
+        writer.append(path)
+    }
+
+    /// Reads a datafile from disk into a new datafile with the default configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::load("test.txt").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read from, or is otherwise
+    /// corrupted.
+    pub fn load<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut datafile = Self::default();
+        datafile.read(path)?;
+        Ok(datafile)
+    }
+
+    /// Writes a datafile to disk. This is an alias for `write`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written to.
+    #[inline]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.write(path)
+    }
+
+    /// Reads a datafile from disk, into the current datafile. Any values and nodes already present
+    /// are discarded first, the configuration is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read("test.txt").unwrap();
+    ///
+    /// // The Datafile is now populated with the contents of test.txt
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read from, or is otherwise
+    /// corrupted.
+    pub fn read<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let reader = Reader::new(self);
+        reader.read(path)
+    }
+
+    /// Does the same as `read`, but replaces invalid UTF-8 in the file with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of failing.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read from.
+    pub fn read_lossy<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let reader = Reader::new(self);
+        reader.read_lossy(path)
+    }
+
+    /// Does the same as `read`, but parses the file with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olc_datafile_rust::{Datafile, ReadOptions};
+    /// let options = ReadOptions {
+    ///     inline_comments: true,
+    ///     record_source_lines: true,
+    ///     ..ReadOptions::default()
+    /// };
+    ///
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read_with("test.txt", &options).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read from, or is otherwise
+    /// corrupted.
+    pub fn read_with<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &ReadOptions,
+    ) -> std::io::Result<()> {
+        let reader = Reader::with_options(self, options.clone());
+        reader.read(path)
+    }
+
+    /// Reads a datafile from a byte slice, e.g. a config embedded with `include_bytes!`, into the
+    /// current datafile. This behaves like `read`, without touching the file system.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read_bytes(b"name = Javid\n").unwrap();
+    ///
+    /// assert_eq!(datafile.get("name").get_string(0), "Javid");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data isn't valid UTF-8.
+    pub fn read_bytes(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let reader = Reader::new(self);
+        reader.read_bytes(data)
+    }
+
+    /// Does the same as `read_bytes`, but replaces invalid UTF-8 with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of failing.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed.
+    pub fn read_bytes_lossy(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let reader = Reader::new(self);
+        reader.read_bytes_lossy(data)
+    }
+
+    /// Does the same as `read`, but reads a gzip-compressed file, e.g. one written by `write_gz`.
+    /// The size limit of the reader applies to the decompressed data. Requires the `gzip`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read_gz("save.txt.gz").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read from, isn't valid gzip data,
+    /// or is otherwise corrupted.
+    #[cfg(feature = "gzip")]
+    pub fn read_gz<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let decoder = flate2::read::GzDecoder::new(std::fs::File::open(path)?);
+        Reader::new(self).read_from(std::io::BufReader::new(decoder))
+    }
+
+    /// Does the same as `write`, but compresses the file with gzip. The text format itself stays
+    /// the same, so the file can also be decompressed with other tools. Requires the `gzip`
+    /// feature.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written to.
+    #[cfg(feature = "gzip")]
+    pub fn write_gz<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        self.write_into(&mut encoder, &WriteOptions::default())?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Sets a string value to the given index. Note that if the index is higher than the current
+    /// length of the list, the list will be extended with empty string values. There is no upper
+    /// bound, so a bad index can cause a huge allocation. Use `try_set_string` for indices that
+    /// can't be trusted.
+    pub fn set_string(&mut self, value: &str, index: usize) {
+        if index >= self.contents.len() {
+            self.contents.resize(index + 1, String::new());
+        }
+
+        self.contents[index] = value.to_string();
+
+        if let Ok(position) = self.nulls.binary_search(&index) {
+            self.nulls.remove(position);
+        }
+
+        if let Ok(position) = self.reals.binary_search(&index) {
+            self.reals.remove(position);
+        }
+    }
+
+    /// Marks the value at the given index as null, i.e. absent rather than empty, extending the
+    /// list like `set_string` does. Its content is an empty string. Null values are only written
+    /// and read as such if a null token is configured, see `WriteOptions::null_token` and
+    /// `ReadOptions::null_token`. Otherwise they're written as empty values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string("", 0);
+    /// datafile.set_null(1);
+    ///
+    /// assert!(!datafile.is_null(0));
+    /// assert!(datafile.is_null(1));
+    /// assert_eq!(datafile.get_string(1), "");
+    /// ```
+    pub fn set_null(&mut self, index: usize) {
+        self.set_string("", index);
+
+        if let Err(position) = self.nulls.binary_search(&index) {
+            self.nulls.insert(position, index);
+        }
+    }
+
+    /// Checks whether the value at the given index is null, see `set_null`. Values that are out
+    /// of bounds aren't null, they don't exist at all.
+    #[inline]
+    #[must_use]
+    pub fn is_null(&self, index: usize) -> bool {
+        self.nulls.binary_search(&index).is_ok()
+    }
+
+    /// Does the same as `set_string`, but refuses to extend the list beyond `max_value_count`
+    /// values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.max_value_count = 4;
+    ///
+    /// assert!(datafile.try_set_string("fine", 3).is_ok());
+    /// assert!(datafile.try_set_string("too far", 4).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the index is not below `max_value_count`.
+    pub fn try_set_string(&mut self, value: &str, index: usize) -> Result<(), DatafileError> {
+        if index >= self.max_value_count {
+            return Err(DatafileError::IndexOutOfRange {
+                index,
+                max_value_count: self.max_value_count,
+            });
+        }
+
+        self.set_string(value, index);
+        Ok(())
+    }
+
+    /// Modifies the string value at the given index in place. If the index is out of bounds, the
+    /// list is extended like `set_string` does and the closure receives an empty string. The value
+    /// is no longer null afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.update_value(0, |value| value.push_str("Javid"));
+    /// datafile.update_value(0, |value| value.make_ascii_uppercase());
+    ///
+    /// assert_eq!(datafile.get_string(0), "JAVID");
+    /// ```
+    pub fn update_value<F: FnOnce(&mut String)>(&mut self, index: usize, f: F) {
+        if index >= self.contents.len() {
+            self.contents.resize(index + 1, String::new());
+        }
+
+        f(&mut self.contents[index]);
+
+        if let Ok(position) = self.nulls.binary_search(&index) {
+            self.nulls.remove(position);
+        }
+
+        if let Ok(position) = self.reals.binary_search(&index) {
+            self.reals.remove(position);
+        }
+    }
+
+    /// Gets a string value from the given index. If the index is out of bounds, an empty string
+    /// will be returned.
+    #[inline]
+    #[must_use]
+    pub fn get_string(&self, index: usize) -> String {
+        self.contents.get(index).unwrap_or(&String::new()).clone()
+    }
+
+    /// Appends a real (numeric) value to the datafile.
+    #[inline]
+    pub fn set_real(&mut self, value: f32, index: usize) {
+        self.set_value(value, index);
+
+        if let Err(position) = self.reals.binary_search(&index) {
+            self.reals.insert(position, index);
+        }
+    }
+
+    /// Does the same as `set_real`, but formats the value with a fixed number of decimals,
+    /// rounding it if necessary. With no decimals, the value is written without a decimal point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_real_precision(1.0 / 3.0, 0, 2);
+    ///
+    /// assert_eq!(datafile.get_string(0), "0.33");
+    /// assert_eq!(datafile.get_real(0), 0.33);
+    /// ```
+    pub fn set_real_precision(&mut self, value: f32, index: usize, decimals: usize) {
+        self.set_string(&format!("{value:.decimals$}"), index);
+    }
+
+    /// Gets a real (numeric) value from the given index. If the index is out of bounds, or the
+    /// value cannot be parsed as a real, 0.0 will be returned.
+    #[inline]
+    #[must_use]
+    pub fn get_real(&self, index: usize) -> f32 {
+        self.get_value(index)
+    }
+
+    /// Does the same as `get_real`, but clamps the value into the range `[min, max]`. Values that
+    /// are missing, can't be parsed or are NaN are read as 0.0, and are clamped as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["1.5", "-3", "nan"]);
+    ///
+    /// assert_eq!(datafile.get_real_clamped(0, 0.0, 1.0), 1.0);
+    /// assert_eq!(datafile.get_real_clamped(1, -1.0, 1.0), -1.0);
+    /// assert_eq!(datafile.get_real_clamped(2, 0.5, 1.0), 0.5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`, or either of them is NaN.
+    #[must_use]
+    pub fn get_real_clamped(&self, index: usize, min: f32, max: f32) -> f32 {
+        let value = self.get_real(index);
+        let value = if value.is_nan() { 0.0 } else { value };
+
+        value.clamp(min, max)
+    }
+
+    /// Sets an integer value to the datafile.
+    #[inline]
+    pub fn set_integer(&mut self, value: i32, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Does the same as `set_integer`, but writes the value in hexadecimal notation, e.g. `0xFF`
+    /// for register values or colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_integer_hex(255, 0);
+    /// datafile.set_integer_hex(-16, 1);
+    ///
+    /// assert_eq!(datafile.get_string_list(), ["0xFF", "-0x10"]);
+    /// assert_eq!(datafile.get_integer(0), 255);
+    /// ```
+    #[inline]
+    pub fn set_integer_hex(&mut self, value: i32, index: usize) {
+        self.set_string(&to_hex(value), index);
+    }
+
+    /// Gets an integer value from the given index. If the index is out of bounds, or the value
+    /// cannot be parsed as an integer, 0 will be returned. Real values will be truncated, not
+    /// rounded. Hexadecimal, binary and octal literals like `0xFF`, `0b1010` or `0o17` are
+    /// supported as well, just like digits grouped in threes by `,` or `_`, e.g. `1,500` or
+    /// `1,000,000`. Any other `,` is a decimal comma, so `1,5` is read as `1`.
+    #[inline]
+    #[must_use]
+    pub fn get_integer(&self, index: usize) -> i32 {
+        self.get_value(index)
+    }
+
+    /// Does the same as `get_integer`, but clamps the value into the range `[min, max]`. Values
+    /// that are missing or can't be parsed are read as 0, and are clamped as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    #[inline]
+    #[must_use]
+    pub fn get_integer_clamped(&self, index: usize, min: i32, max: i32) -> i32 {
+        self.get_integer(index).clamp(min, max)
+    }
+
+    /// Sets an unsigned integer value to the datafile, for counts and sizes that can't be
+    /// negative.
+    #[inline]
+    pub fn set_unsigned(&mut self, value: u32, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Gets an unsigned integer value from the given index. If the index is out of bounds, or the
+    /// value cannot be parsed, 0 will be returned. Negative values are clamped to 0 and real values
+    /// are truncated.
+    #[inline]
+    #[must_use]
+    pub fn get_unsigned(&self, index: usize) -> u32 {
+        self.get_value(index)
+    }
+
+    /// Sets a 64-bit integer value to the datafile, e.g. an ID that doesn't fit into an `i32`.
+    #[inline]
+    pub fn set_long(&mut self, value: i64, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Gets a 64-bit integer value from the given index. If the index is out of bounds, or the
+    /// value cannot be parsed, 0 will be returned. Unlike `get_integer`, reals aren't accepted,
+    /// so large values are never rounded through a float. Values that don't fit are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_long(1_234_567_890_123_456_789, 0);
+    ///
+    /// assert_eq!(datafile.get_long(0), 1_234_567_890_123_456_789);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get_long(&self, index: usize) -> i64 {
+        self.get_value(index)
+    }
+
+    /// Sets an unsigned 64-bit integer value to the datafile.
+    #[inline]
+    pub fn set_unsigned_long(&mut self, value: u64, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Gets an unsigned 64-bit integer value from the given index, coerced the same way as
+    /// `get_long`. Negative values are clamped to 0.
+    #[inline]
+    #[must_use]
+    pub fn get_unsigned_long(&self, index: usize) -> u64 {
+        self.get_value(index)
+    }
+
+    /// Sets a boolean value to the datafile, written as `true` or `false`.
+    #[inline]
+    pub fn set_bool(&mut self, value: bool, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Gets a boolean value from the given index. `true`, `yes`, `on` and `1` are read as `true`,
+    /// ignoring case. Anything else, or an index that is out of bounds, is read as `false`.
+    #[inline]
+    #[must_use]
+    pub fn get_bool(&self, index: usize) -> bool {
+        self.get_value(index)
+    }
+
+    /// Does the same as `get_bool`, but only accepts `false`, `no`, `off` and `0` as `false`. Any
+    /// other value, or an index that is out of bounds, returns the given default instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["Yes", "off", "maybe"]);
+    ///
+    /// assert!(datafile.get_bool_or(0, false));
+    /// assert!(!datafile.get_bool_or(1, true));
+    /// assert!(datafile.get_bool_or(2, true));
+    /// assert!(datafile.get_bool_or(3, true));
+    /// ```
+    #[must_use]
+    pub fn get_bool_or(&self, index: usize, default: bool) -> bool {
+        self.contents
+            .get(index)
+            .and_then(|value| parse_bool(value))
+            .unwrap_or(default)
+    }
+
+    /// Sets a single character value to the datafile, e.g. a keybinding.
+    #[inline]
+    pub fn set_char(&mut self, value: char, index: usize) {
+        self.set_value(value, index);
+    }
+
+    /// Gets a single character value from the given index. Only the first character of the value
+    /// is returned. If the index is out of bounds, or the value is empty, `'\0'` will be returned.
+    #[inline]
+    #[must_use]
+    pub fn get_char(&self, index: usize) -> char {
+        self.get_value(index)
+    }
+
+    /// Gets a value from the given index that is restricted to a known set of variants, e.g. the
+    /// difficulty `easy`, `normal` or `hard`. If the value isn't one of the variants, or the index
+    /// is out of bounds, the default is returned instead. Variants are matched ignoring case if
+    /// the datafile is case-insensitive, and returned with the casing given in `variants`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// const DIFFICULTIES: &[&str] = &["easy", "normal", "hard"];
+    ///
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["hard", "impossible"]);
+    ///
+    /// assert_eq!(datafile.get_enum(0, DIFFICULTIES, "normal"), "hard");
+    /// assert_eq!(datafile.get_enum(1, DIFFICULTIES, "normal"), "normal");
+    /// ```
+    #[must_use]
+    pub fn get_enum<'a>(&self, index: usize, variants: &'a [&str], default: &'a str) -> &'a str {
+        self.contents
+            .get(index)
+            .and_then(|value| {
+                variants
+                    .iter()
+                    .find(|variant| self.names_match(value, variant))
+            })
+            .map_or(default, |variant| *variant)
+    }
+
+    /// Sets a value to the given index after checking that it's one of the given variants, see
+    /// `get_enum`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value isn't one of the variants, or if the index
+    /// is not below `max_value_count`.
+    pub fn set_enum(
+        &mut self,
+        value: &str,
+        index: usize,
+        variants: &[&str],
+    ) -> Result<(), DatafileError> {
+        let Some(variant) = variants
+            .iter()
+            .find(|variant| self.names_match(value, variant))
+        else {
+            return Err(DatafileError::UnknownVariant {
+                value: value.to_string(),
+                variants: variants
+                    .iter()
+                    .map(|variant| (*variant).to_string())
+                    .collect(),
+            });
+        };
+
+        self.try_set_string(variant, index)
+    }
+
+    /// Sets a value of any serializable type to the given index, extending the list like
+    /// `set_string` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_value(24, 0);
+    /// datafile.set_value(1.88, 1);
+    ///
+    /// assert_eq!(datafile.get_value::<i32>(0), 24);
+    /// assert_eq!(datafile.get_value::<f32>(1), 1.88);
+    /// ```
+    #[inline]
+    pub fn set_value<T: Serialize>(&mut self, value: T, index: usize) {
+        self.set_string(&value.serialize(), index);
+    }
+
+    /// Gets a value of any deserializable type from the given index. Missing or malformed values
+    /// are coerced the same way the typed getters like `get_integer` do.
+    #[inline]
+    #[must_use]
+    pub fn get_value<'a, T: Deserialize<'a>>(&'a self, index: usize) -> T {
+        T::deserialize(self.contents.get(index).map_or("", String::as_str))
+    }
+
+    /// Replaces all values of the datafile with the given strings.
+    pub fn set_string_list(&mut self, values: &[&str]) {
+        self.contents = values.iter().map(|value| (*value).to_string()).collect();
+        self.nulls.clear();
+        self.reals.clear();
+    }
+
+    /// Replaces all values of the datafile with the ones in a list formatted like the right-hand
+    /// side of a line in a file. It's split and unquoted the same way the reader does it, using
+    /// this datafile's list separator and quotation mark, but keeping the whitespace within
+    /// quotation marks. Useful to set pre-formatted lists.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_raw("a, \"b, c\", d");
+    ///
+    /// assert_eq!(datafile.get_string_list(), ["a", "b, c", "d"]);
+    /// ```
+    pub fn set_raw(&mut self, raw: &str) {
+        self.contents = parse_raw_values(self, raw);
+        self.nulls.clear();
+        self.reals.clear();
+    }
+
+    /// Returns all values of the datafile as strings.
+    #[inline]
+    #[must_use]
+    pub fn get_string_list(&self) -> Vec<String> {
+        self.contents.clone()
+    }
+
+    /// Replaces all values of the datafile with the given reals.
+    pub fn set_real_list(&mut self, values: &[f32]) {
+        self.contents = values.iter().map(Serialize::serialize).collect();
+        self.nulls.clear();
+        self.reals = (0..values.len()).collect();
+    }
+
+    /// Returns all values of the datafile as reals, coerced the same way as `get_real`.
+    #[must_use]
+    pub fn get_real_list(&self) -> Vec<f32> {
+        self.contents
+            .iter()
+            .map(|value| f32::deserialize(value))
+            .collect()
+    }
+
+    /// Checks whether every value of the datafile is a real, e.g. to plot them. Empty values are
+    /// skipped, so a list without any values counts as numeric as well.
+    #[must_use]
+    pub fn is_numeric_list(&self) -> bool {
+        self.contents
+            .iter()
+            .all(|value| value.is_empty() || parse_real(value).is_some())
+    }
+
+    /// Returns all values of the datafile as reals, or `None` if any of them isn't numeric, see
+    /// `is_numeric_list`. Empty values are read as 0.0, just like `get_real` does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["1.5", "2", "-3,5"]);
+    /// assert_eq!(datafile.as_real_vec(), Some(vec![1.5, 2.0, -3.5]));
+    ///
+    /// datafile.set_string("three", 1);
+    /// assert_eq!(datafile.as_real_vec(), None);
+    /// ```
+    #[must_use]
+    pub fn as_real_vec(&self) -> Option<Vec<f32>> {
+        self.contents
+            .iter()
+            .map(|value| {
+                if value.is_empty() {
+                    Some(0.0)
+                } else {
+                    parse_real(value)
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces all values of the datafile with the given integers.
+    pub fn set_integer_list(&mut self, values: &[i32]) {
+        self.contents = values.iter().map(Serialize::serialize).collect();
+        self.nulls.clear();
+        self.reals.clear();
+    }
+
+    /// Returns all values of the datafile as integers, coerced the same way as `get_integer`.
+    #[must_use]
+    pub fn get_integer_list(&self) -> Vec<i32> {
+        self.contents
+            .iter()
+            .map(|value| i32::deserialize(value))
+            .collect()
+    }
+
+    /// Checks if any of the datafile's values is equal to the given string.
+    #[inline]
+    #[must_use]
+    pub fn contains_value(&self, value: &str) -> bool {
+        self.contents.iter().any(|content| content == value)
+    }
+
+    /// Returns the index of the first value equal to the given string, or `None` if there is no
+    /// such value.
+    #[inline]
+    #[must_use]
+    pub fn index_of_value(&self, value: &str) -> Option<usize> {
+        self.contents.iter().position(|content| content == value)
+    }
+
+    /// Returns all values of the datafile as the raw strings they are stored as, without cloning
+    /// them like `get_string` does.
+    #[inline]
+    #[must_use]
+    pub fn values_slice(&self) -> &[String] {
+        &self.contents
+    }
+
+    /// Returns the first value of the datafile, or `None` if it has no values. Unlike
+    /// `get_string(0)`, this tells a missing value apart from an empty one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// assert_eq!(datafile.first_string(), None);
+    ///
+    /// datafile.set_string_list(&["", "lua"]);
+    /// assert_eq!(datafile.first_string(), Some(""));
+    /// assert_eq!(datafile.last_string(), Some("lua"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn first_string(&self) -> Option<&str> {
+        self.contents.first().map(String::as_str)
+    }
+
+    /// Returns the last value of the datafile, or `None` if it has no values.
+    #[inline]
+    #[must_use]
+    pub fn last_string(&self) -> Option<&str> {
+        self.contents.last().map(String::as_str)
+    }
+
+    /// Returns the first value of the datafile as a real, coerced the same way as `get_real`, or
+    /// `None` if it has no values.
+    #[inline]
+    #[must_use]
+    pub fn first_real(&self) -> Option<f32> {
+        self.first_string().map(f32::deserialize)
+    }
+
+    /// Returns the last value of the datafile as a real, coerced the same way as `get_real`, or
+    /// `None` if it has no values.
+    #[inline]
+    #[must_use]
+    pub fn last_real(&self) -> Option<f32> {
+        self.last_string().map(f32::deserialize)
+    }
+
+    /// Returns the first value of the datafile as an integer, coerced the same way as
+    /// `get_integer`, or `None` if it has no values.
+    #[inline]
+    #[must_use]
+    pub fn first_integer(&self) -> Option<i32> {
+        self.first_string().map(i32::deserialize)
+    }
+
+    /// Returns the last value of the datafile as an integer, coerced the same way as
+    /// `get_integer`, or `None` if it has no values.
+    #[inline]
+    #[must_use]
+    pub fn last_integer(&self) -> Option<i32> {
+        self.last_string().map(i32::deserialize)
+    }
+
+    /// Returns all values of the datafile for which the predicate returns `true`, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.set_string_list(&["map.png", "song.ogg", "tiles.png"]);
+    ///
+    /// assert_eq!(
+    ///     datafile.values_matching(|value| value.ends_with(".png")),
+    ///     ["map.png", "tiles.png"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn values_matching<F: Fn(&str) -> bool>(&self, f: F) -> Vec<&str> {
+        self.contents
+            .iter()
+            .map(String::as_str)
+            .filter(|value| f(value))
+            .collect()
+    }
+
+    /// Returns the number of values of the datafile for which the predicate returns `true`.
+    #[must_use]
+    pub fn count_values_matching<F: Fn(&str) -> bool>(&self, f: F) -> usize {
+        self.contents.iter().filter(|value| f(value)).count()
+    }
+
+    /// Returns the number of items in the datafile. Does not include child node's contents.
+    #[inline]
+    #[must_use]
+    pub fn get_value_count(&self) -> usize {
+        self.contents.len()
+    }
+
+    /// Returns the number of direct child nodes. Comments are not counted, see `comment_count`.
+    #[must_use]
+    pub fn child_count(&self) -> usize {
+        self.object_vec.len() - self.comment_count()
+    }
+
+    /// Returns the number of standalone comments directly inside this node.
+    #[must_use]
+    pub fn comment_count(&self) -> usize {
+        self.object_vec
+            .iter()
+            .filter(|(_, node)| node.is_comment)
+            .count()
+    }
+
+    /// Returns whether this node has no child nodes, only values. Comments are ignored.
+    #[inline]
+    #[must_use]
+    pub fn is_leaf(&self) -> bool {
+        self.child_count() == 0
+    }
+
+    /// Returns whether this node has neither values nor child nodes. Comments are ignored.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty() && self.is_leaf()
+    }
+
+    /// Returns the number of nodes below this node, counting children, their children and so
+    /// forth. The node itself and comments are not counted.
+    #[must_use]
+    pub fn total_node_count(&self) -> usize {
+        self.object_vec
+            .iter()
+            .filter(|(_, node)| !node.is_comment)
+            .map(|(_, node)| 1 + node.total_node_count())
+            .sum()
+    }
+
+    /// Returns the number of values stored in this node and all nodes below it. Unlike
+    /// `get_value_count`, this includes the contents of child nodes.
+    #[must_use]
+    pub fn total_value_count(&self) -> usize {
+        self.contents.len()
+            + self
+                .object_vec
+                .iter()
+                .map(|(_, node)| node.total_value_count())
+                .sum::<usize>()
+    }
+
+    /// Returns the number of the line this node was parsed from, starting at 1. For nodes holding
+    /// values, this is the first line assigning to them. Only recorded if enabled through
+    /// `ReadOptions::record_source_lines`, `None` otherwise or for nodes that weren't read from a
+    /// file.
+    #[inline]
+    #[must_use]
+    pub const fn source_line(&self) -> Option<usize> {
+        self.source_line
+    }
+
+    /// Returns a child node with the given name. If the node does not exist, it will be created.
+    /// This can be chained to create a path of nodes. For example, `datafile.get("a").get("b")`
+    /// will either return the node `b` under `a`, or create it if it does not exist.
+    #[inline]
+    pub fn get(&mut self, name: &str) -> &mut Self {
+        self.entry(name)
+    }
+
+    /// Does the same as `get`. An existing node is found with a single map lookup, half as many as
+    /// checking for the node before indexing it, as the `get (existing)` case of the lookup
+    /// benchmark in `benches/datafile.rs` measures.
+    ///
+    /// A new node takes a second lookup to insert it. A single `HashMap::entry` call would need
+    /// an owned key up front, allocating a copy of the name even when the node already exists,
+    /// and the entry API taking a borrowed key, `raw_entry`, isn't stable. The name is stored
+    /// once and shared between the map and the list of child nodes through a cloned `Arc`.
+    #[inline]
+    pub fn entry(&mut self, name: &str) -> &mut Self {
+        self.entry_interned(name, None)
+    }
+
+    /// Checks if a child node or value with the given name exists.
+    #[inline]
+    #[must_use]
+    pub fn has_property(&self, name: &str) -> bool {
+        self.object_map.contains_key(&*self.map_key(name))
+    }
+
+    /// Renames the child node `old` to `new`, keeping its contents, children and position. Should
+    /// multiple siblings be named `old`, only the first one is renamed.
+    ///
+    /// Returns `false` without changing anything if there is no node named `old`, or if a node
+    /// named `new` already exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("old").set_string("value", 0);
+    ///
+    /// assert!(datafile.rename("old", "new"));
+    /// assert!(!datafile.has_property("old"));
+    /// assert_eq!(datafile.get("new").get_string(0), "value");
+    /// ```
+    pub fn rename(&mut self, old: &str, new: &str) -> bool {
+        let Some(&index) = self.object_map.get(&*self.map_key(old)) else {
+            return false;
+        };
+
+        // Only changing the casing in case-insensitive mode doesn't collide with the node itself.
+        if self.map_key(old) != self.map_key(new) && self.has_property(new) {
+            return false;
+        }
+
+        self.object_vec[index].0 = new.into();
+        self.rebuild_object_map();
+        true
+    }
+
+    /// Removes the child node with the given name and returns it, along with everything below
+    /// it. Should multiple siblings share the name, only the first one is removed. Together with
+    /// `insert`, this moves a node to another parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("inventory").get("sword").set_integer(1, 0);
+    ///
+    /// let sword = datafile.get("inventory").take("sword").unwrap();
+    /// datafile.get("chest").insert("sword", sword);
+    ///
+    /// assert!(!datafile.get("inventory").has_property("sword"));
+    /// assert_eq!(datafile.get("chest").get("sword").get_integer(0), 1);
+    /// ```
+    pub fn take(&mut self, name: &str) -> Option<Self> {
+        let index = *self.object_map.get(&*self.map_key(name))?;
+        let (_, node) = self.object_vec.remove(index);
+        self.rebuild_object_map();
+        Some(node)
+    }
+
+    /// Inserts a node as the child with the given name. An existing child with that name is
+    /// replaced, keeping its position, otherwise the node is appended. The node keeps its own
+    /// configuration, e.g. its list separator.
+    pub fn insert(&mut self, name: &str, node: Self) {
+        match self.object_map.get(&*self.map_key(name)) {
+            Some(&index) => self.object_vec[index].1 = node,
+            None => self.push_child(name, node),
+        }
+    }
+
+    /// Does the same as `insert`, but refuses to replace an existing child node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// let mut node = Datafile::new(None, None);
+    /// node.set_string("Javid", 0);
+    ///
+    /// assert!(datafile.try_insert("name", node.clone()).is_ok());
+    /// assert_eq!(datafile.try_insert("name", node.clone()), Err(node));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return the node back if a child with the given name already exists.
+    // Handing the node back lets the caller insert it elsewhere, like `HashMap::try_insert` does.
+    #[allow(clippy::result_large_err)]
+    pub fn try_insert(&mut self, name: &str, node: Self) -> Result<(), Self> {
+        if self.has_property(name) {
+            return Err(node);
+        }
+
+        self.push_child(name, node);
+        Ok(())
+    }
+
+    /// Checks whether a key can be written to a file and read back unchanged. Keys containing
+    /// reserved characters such as `=`, braces or a leading `#` are still valid, as the writer
+    /// delimits them by quotation marks. Keys that are empty, contain quotation marks or line
+    /// breaks, or start or end with whitespace can't be represented in the file format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// assert!(Datafile::is_valid_key("a=b"));
+    /// assert!(!Datafile::is_valid_key("\"quoted\""));
+    /// assert!(!Datafile::is_valid_key(" padded "));
+    /// ```
+    #[must_use]
+    pub fn is_valid_key(name: &str) -> bool {
+        Self::is_valid_key_quoted(name, DEFAULT_QUOTE_CHAR)
+    }
+
+    /// Does the same as `is_valid_key`, for a datafile using the given quote character.
+    pub(crate) fn is_valid_key_quoted(name: &str, quote_char: char) -> bool {
+        !name.is_empty() && name.trim() == name && !name.contains([quote_char, '\n', '\r'])
+    }
+
+    /// Returns all items of this node in the order they appear in the datafile, whether they are
+    /// comments, values or child nodes. A node is returned as a value if it has no children,
+    /// just like the writer writes it as a single line.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::{Datafile, Entry};
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("name").set_string("Javid", 0);
+    /// datafile.get("pc").get("ram").set_integer(32, 0);
+    ///
+    /// for entry in datafile.entries() {
+    ///     match entry {
+    ///         Entry::Comment(text) => println!("{text}"),
+    ///         Entry::Value { key, values } => println!("{key} = {}", values.join(", ")),
+    ///         Entry::Node(name, node) => println!("{name} has {} children", node.child_count()),
+    ///     }
+    /// }
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.object_vec.iter().map(|(name, node)| {
+            if node.is_comment {
+                Entry::Comment(name)
+            } else if node.object_vec.is_empty() {
+                Entry::Value {
+                    key: name,
+                    values: &node.contents,
+                }
+            } else {
+                Entry::Node(name, node)
+            }
+        })
+    }
+
+    /// Returns all child nodes with the given name, in the order they appear in the datafile.
+    /// Comments are never included. The result is empty if no node with that name exists.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read("test.txt").unwrap();
+    ///
+    /// for enemy in datafile.get_all("enemy") {
+    ///     println!("{:?}", enemy);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn get_all(&self, name: &str) -> Vec<&Self> {
+        self.object_vec
+            .iter()
+            .filter(|(node_name, node)| !node.is_comment && self.names_match(node_name, name))
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// Appends a new child node with the given name and returns it, even if siblings with that
+    /// name already exist. This builds an array of nodes, which is written as repeated blocks
+    /// sharing the name and read back in the same order, see `array_items`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.push_array_item("enemy").get("name").set_string("orc", 0);
+    /// datafile.push_array_item("enemy").get("name").set_string("goblin", 0);
+    ///
+    /// let names: Vec<_> = datafile
+    ///     .array_items("enemy")
+    ///     .map(|enemy| enemy.try_get("name").unwrap().get_string(0))
+    ///     .collect();
+    /// assert_eq!(names, ["orc", "goblin"]);
+    /// ```
+    pub fn push_array_item(&mut self, name: &str) -> &mut Self {
+        let index = self.object_vec.len();
+        self.push_child(name, self.new_child());
+        &mut self.object_vec[index].1
+    }
+
+    /// Returns an iterator over all child nodes with the given name, in the order they appear in.
+    /// Unlike `get_all`, nothing is collected. Comments are never included.
+    pub fn array_items<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Self> {
+        self.object_vec
+            .iter()
+            .filter(move |(node_name, node)| !node.is_comment && self.names_match(node_name, name))
+            .map(|(_, node)| node)
+    }
+
+    /// Returns the child node with the given name without creating it, or `None` if it doesn't
+    /// exist.
+    #[must_use]
+    pub fn try_get(&self, name: &str) -> Option<&Self> {
+        self.object_map
+            .get(&*self.map_key(name))
+            .map(|&index| &self.object_vec[index].1)
+    }
+
+    /// Returns the child node with the given name without creating it, or the given default if it
+    /// doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut defaults = Datafile::new(None, None);
+    /// defaults.set_integer(60, 0);
+    ///
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("fps").set_integer(144, 0);
+    ///
+    /// assert_eq!(datafile.get_or("fps", &defaults).get_integer(0), 144);
+    /// assert_eq!(datafile.get_or("vsync", &defaults).get_integer(0), 60);
+    /// ```
+    #[must_use]
+    pub fn get_or<'a>(&'a self, name: &str, default: &'a Self) -> &'a Self {
+        self.try_get(name).unwrap_or(default)
+    }
+
+    /// Returns the datafile at a given path using dot notation. If no node exists at the given
+    /// path, they will get inserted.
+    ///
+    /// A segment may select one of multiple siblings sharing a name with an index in square
+    /// brackets, e.g. `a.b[2].c` descends into the third node named `b` below `a`. The index
+    /// always refers to child nodes, values are accessed on the returned node as usual. Without
+    /// an index, the first node with that name is used. Should there be fewer siblings than the
+    /// index requires, empty ones are inserted.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read("test.txt").unwrap();
+    ///
+    /// let node = datafile.get_property("a.b.c");
+    /// let item = datafile.get_property("inventory.item[1].name");
+    /// ```
+    pub fn get_property(&mut self, name: &str) -> &Self {
+        self.get_path_mut(name)
+    }
+
+    /// Does the same as `get_property`, but selects the sibling at `index` for the last segment
+    /// of the path, i.e. `name[index]` in array notation.
+    pub fn get_indexed_property(&mut self, name: &str, index: usize) -> &Self {
+        self.get_property(&format!("{}[{}]", name, index))
+    }
+
+    /// Returns the datafile at a given path, using the same notation as `get_property`. Unlike
+    /// `get_property`, no nodes are inserted. `None` is returned as soon as a segment of the path
+    /// doesn't exist. Paths that are resolved repeatedly can be parsed once with `DatafilePath`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("a").get("b").set_integer(1, 0);
+    ///
+    /// assert_eq!(datafile.get_path("a.b").map(|node| node.get_integer(0)), Some(1));
+    /// assert!(datafile.get_path("a.c").is_none());
+    /// assert!(datafile.get_path("a.b[1]").is_none());
+    /// ```
+    #[must_use]
+    pub fn get_path(&self, path: &str) -> Option<&Self> {
+        path.split('.').try_fold(self, |node, segment| {
+            let (name, index) = Self::parse_path_segment(segment);
+            node.get_nth(name, index)
+        })
+    }
+
+    /// Checks whether a node exists at the given path, using the same notation as
+    /// `get_property`. Unlike `get_property`, no nodes are inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("pc").get("ram").set_integer(32, 0);
+    ///
+    /// assert!(datafile.contains_path("pc.ram"));
+    /// assert!(!datafile.contains_path("pc.gpu.vram"));
+    /// assert!(!datafile.get("pc").has_property("gpu"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn contains_path(&self, path: &str) -> bool {
+        self.get_path(path).is_some()
+    }
+
+    /// Returns every node below this one whose path matches a pattern, along with that path, in
+    /// the order they appear in the tree. Patterns use the notation of `get_path`, where a `*`
+    /// segment matches any single segment and a `**` segment any number of them, including none.
+    /// Comments are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// let world = datafile.get("world");
+    /// world.get("player").get("health").set_integer(100, 0);
+    /// world.get("enemy").get("health").set_integer(20, 0);
+    ///
+    /// let paths: Vec<_> = datafile
+    ///     .glob("world.*.health")
+    ///     .into_iter()
+    ///     .map(|(path, _)| path)
+    ///     .collect();
+    ///
+    /// assert_eq!(paths, ["world.player.health", "world.enemy.health"]);
+    /// ```
+    #[must_use]
+    pub fn glob(&self, pattern: &str) -> Vec<(String, &Self)> {
+        let pattern: Vec<_> = pattern.split('.').collect();
+        let mut matches = vec![];
+        self.glob_into(self, &pattern, "", &mut vec![], &mut matches);
+        matches
+    }
+
+    /// Returns the datafile at a given path for editing, using the same notation as
+    /// `get_property`. Missing nodes along the path are created, just like `get` does.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile
+    ///     .get_path_mut("config.video.resolution")
+    ///     .set_string_list(&["1920", "1080"]);
+    ///
+    /// assert_eq!(
+    ///     datafile.get("config").get("video").get("resolution").get_integer(1),
+    ///     1080
+    /// );
+    /// ```
+    pub fn get_path_mut(&mut self, path: &str) -> &mut Self {
+        let mut node = self;
+
+        for segment in path.split('.') {
+            let (name, index) = Self::parse_path_segment(segment);
+            node = node.get_nth_mut(name, index);
+        }
+
+        node
+    }
+
+    /// Compares this datafile with another one and returns everything that changed, going from
+    /// this datafile to the other one. Child nodes are matched by their name and their index
+    /// among the siblings sharing it, comments are ignored.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::{Change, Datafile};
+    /// let mut old = Datafile::new(None, None);
+    /// old.get("pc").get("ram").set_integer(16, 0);
+    ///
+    /// let mut new = old.clone();
+    /// new.get("pc").get("ram").set_integer(32, 0);
+    ///
+    /// assert_eq!(
+    ///     old.diff(&new),
+    ///     [Change::ValueModified {
+    ///         path: "pc.ram".to_string(),
+    ///         index: 0,
+    ///         old: "16".to_string(),
+    ///         new: "32".to_string(),
+    ///     }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<Change> {
+        let mut changes = vec![];
+        diff_nodes(self, other, "", &mut changes);
+        changes
+    }
+
+    /// Flattens the tree below this node into pairs of paths and values, e.g.
+    /// `("some_node.pc.ram", ["32"])`. Paths use the same notation as `get_path`. Every node with
+    /// values is included, as well as nodes that have neither values nor children, so the
+    /// structure can be rebuilt with `from_flat`. Comments are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("pc").get("ram").set_integer(32, 0);
+    ///
+    /// assert_eq!(
+    ///     datafile.flatten(),
+    ///     [("pc.ram".to_string(), vec!["32".to_string()])]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn flatten(&self) -> Vec<(String, Vec<String>)> {
+        let mut pairs = vec![];
+        self.flatten_into("", &mut pairs);
+        pairs
+    }
+
+    /// Rebuilds a tree from pairs of paths and values, as returned by `flatten`. Missing nodes
+    /// along the paths are created in the order they first appear. The new datafile uses the
+    /// default configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let pairs = [("pc.ram".to_string(), vec!["32".to_string()])];
+    /// let mut datafile = Datafile::from_flat(&pairs);
+    ///
+    /// assert_eq!(datafile.get("pc").get("ram").get_integer(0), 32);
+    /// assert_eq!(datafile.flatten(), pairs);
+    /// ```
+    #[must_use]
+    pub fn from_flat(pairs: &[(String, Vec<String>)]) -> Self {
+        let mut datafile = Self::default();
+
+        for (path, values) in pairs {
+            datafile.get_path_mut(path).contents = values.clone();
+        }
+
+        datafile
+    }
+
+    /// Collects the child nodes holding exactly one value into a map of their names and values,
+    /// the counterpart of converting a map into a datafile. Nodes with several or no values, or
+    /// with children of their own, are skipped, as are comments. If multiple nodes share a name,
+    /// the first of them is used, like `get` does.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("name").set_string("Javid", 0);
+    /// datafile.get("code").set_string_list(&["c++", "lua"]);
+    ///
+    /// let map = datafile.to_string_map();
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map["name"], "Javid");
+    /// ```
+    #[must_use]
+    pub fn to_string_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+
+        for (name, node) in &self.object_vec {
+            let [value] = node.contents.as_slice() else {
+                continue;
+            };
+
+            if !node.is_comment && node.is_leaf() {
+                map.entry(name.to_string()).or_insert_with(|| value.clone());
+            }
+        }
+
+        map
+    }
+
+    fn flatten_into(&self, path: &str, pairs: &mut Vec<(String, Vec<String>)>) {
+        for (child_path, _, _, child) in self.indexed_children(path) {
+            if !child.contents.is_empty() || child.is_leaf() {
+                pairs.push((child_path.clone(), child.contents.clone()));
+            }
+
+            child.flatten_into(&child_path, pairs);
+        }
+    }
+
+    /// Checks that the whole tree below this node can be written and read back without losing
+    /// anything. All problems found are collected, instead of stopping at the first one. Nodes are
+    /// identified by their path, joined by dots. Values with leading or trailing whitespace are
+    /// only read back unchanged if `ReadOptions::trim_values` is disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::{Datafile, DatafileError};
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("name").set_string("Javid", 0);
+    /// assert_eq!(datafile.validate(), Ok(()));
+    ///
+    /// datafile.get("quote").set_string("\"hi\"", 0);
+    /// assert_eq!(
+    ///     datafile.validate(),
+    ///     Err(vec![DatafileError::InvalidValue {
+    ///         path: "quote".to_string(),
+    ///         index: 0
+    ///     }])
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error for every key, value or node that can't be written.
+    pub fn validate(&self) -> Result<(), Vec<DatafileError>> {
+        let mut errors = vec![];
+
+        self.visit(|path, node| {
+            let name = path.last().copied().unwrap_or_default();
+            let path = || path.join(".");
+
+            if !Self::is_valid_key_quoted(name, node.quote_char) {
+                errors.push(DatafileError::InvalidKey(path()));
+            }
+
+            for (index, value) in node.contents.iter().enumerate() {
+                if value.contains([node.quote_char, '\n', '\r']) {
+                    errors.push(DatafileError::InvalidValue {
+                        path: path(),
+                        index,
+                    });
+                }
+            }
+
+            if node.contents.is_empty() && node.object_vec.is_empty() {
+                errors.push(DatafileError::EmptyNode(path()));
+            }
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Walks the whole tree below this node and invokes the callback for every child node, parents
+    /// before their children. The callback receives the path segments leading from this node to
+    /// the visited node, including the visited node's own name. Comments are not visited.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.read("test.txt").unwrap();
+    ///
+    /// datafile.visit(|path, node| {
+    ///     println!("{} has {} values", path.join("."), node.get_value_count());
+    /// });
+    /// ```
+    pub fn visit<F: FnMut(&[&str], &Self)>(&self, mut f: F) {
+        self.visit_inner(&mut vec![], &mut f);
+    }
+
+    /// Does the same as `visit`, but allows the callback to modify the visited nodes in place.
+    /// Children are visited after the callback has been invoked on their parent, so nodes added
+    /// by the callback are visited as well.
+    pub fn visit_mut<F: FnMut(&[&str], &mut Self)>(&mut self, mut f: F) {
+        self.visit_mut_inner(&mut vec![], &mut f);
+    }
+
+    /// Walks this node and the whole tree below it, and replaces every value the callback returns
+    /// a replacement for. Values for which it returns `None` are left untouched. Useful for
+    /// migrations, e.g. renaming a value that is used throughout the tree.
+    ///
+    /// # Examples
+    /// ```
+    /// # use olc_datafile_rust::Datafile;
+    /// let mut datafile = Datafile::new(None, None);
+    /// datafile.get("enemy").get("kind").set_string("orc", 0);
+    ///
+    /// datafile.replace_values(|value| (value == "orc").then(|| "goblin".to_string()));
+    /// assert_eq!(datafile.get("enemy").get("kind").get_string(0), "goblin");
+    /// ```
+    pub fn replace_values<F: Fn(&str) -> Option<String>>(&mut self, f: F) {
+        let replace = |node: &mut Self| {
+            for (index, value) in node.contents.iter_mut().enumerate() {
+                // Null values have no content that could be replaced.
+                if node.nulls.binary_search(&index).is_ok() {
+                    continue;
+                }
+
+                if let Some(replacement) = f(value) {
+                    *value = replacement;
+                }
+            }
+        };
+
+        replace(self);
+        self.visit_mut(|_, node| replace(node));
+    }
+
+    fn visit_inner<'a, F: FnMut(&[&str], &Self)>(&'a self, path: &mut Vec<&'a str>, f: &mut F) {
+        for (name, node) in self.object_vec.iter().filter(|(_, node)| !node.is_comment) {
+            path.push(name);
+            f(path, node);
+            node.visit_inner(path, f);
+            path.pop();
+        }
+    }
+
+    fn visit_mut_inner<'a, F: FnMut(&[&str], &mut Self)>(
+        &'a mut self,
+        path: &mut Vec<&'a str>,
+        f: &mut F,
+    ) {
+        for (name, node) in self
+            .object_vec
+            .iter_mut()
+            .filter(|(_, node)| !node.is_comment)
+        {
+            path.push(name);
+            f(path, node);
+            node.visit_mut_inner(path, f);
+            path.pop();
+        }
+    }
+
+    fn glob_into<'a>(
+        &'a self,
+        root: &Self,
+        pattern: &[&str],
+        path: &str,
+        segments: &mut Vec<(&'a str, usize)>,
+        matches: &mut Vec<(String, &'a Self)>,
+    ) {
+        for (child_path, name, index, child) in self.indexed_children(path) {
+            segments.push((name, index));
+
+            if root.glob_matches(pattern, segments) {
+                matches.push((child_path.clone(), child));
+            }
+
+            child.glob_into(root, pattern, &child_path, segments, matches);
+            segments.pop();
+        }
+    }
+
+    /// Checks whether the names and indices of a path match the segments of a glob pattern.
+    fn glob_matches(&self, pattern: &[&str], segments: &[(&str, usize)]) -> bool {
+        match pattern.split_first() {
+            None => segments.is_empty(),
+            Some((&"**", rest)) => {
+                (0..=segments.len()).any(|skipped| self.glob_matches(rest, &segments[skipped..]))
+            }
+            Some((segment, rest)) => {
+                let Some(((name, index), remaining)) = segments.split_first() else {
+                    return false;
+                };
+
+                let (pattern_name, pattern_index) = Self::parse_path_segment(segment);
+                let matches = *segment == "*"
+                    || (self.names_match(pattern_name, name) && pattern_index == *index);
+
+                matches && self.glob_matches(rest, remaining)
+            }
+        }
+    }
+
+    /// Does the same as `entry`, but takes the name of a newly inserted node from the interner,
+    /// if one is given, so nodes sharing a name also share its storage.
+    pub(crate) fn entry_interned(
+        &mut self,
+        name: &str,
+        interner: Option<&mut Interner>,
+    ) -> &mut Self {
+        if let Some(&index) = self.object_map.get(&*self.map_key(name)) {
+            return &mut self.object_vec[index].1;
+        }
+
+        let mut interner = interner;
+        let name = match interner.as_deref_mut() {
+            Some(interner) => interner.intern(name),
+            None => Arc::from(name),
+        };
+
+        let index = self.object_vec.len();
+        self.object_map
+            .insert(self.shared_key(&name, interner), index);
+        self.object_vec.push((name, self.new_child()));
+
+        &mut self.object_vec[index].1
+    }
+
+    #[inline]
+    pub(crate) fn push_object(&mut self, name: impl Into<Arc<str>>, object: Self) {
+        self.object_vec.push((name.into(), object));
+    }
+
+    /// Appends a child node, even if a sibling with the same name already exists. Lookups by
+    /// name keep pointing to the first sibling.
+    pub(crate) fn push_child(&mut self, name: impl Into<Arc<str>>, node: Self) {
+        let name = name.into();
+
+        if !self.object_map.contains_key(&*self.map_key(&name)) {
+            let key = self.shared_key(&name, None);
+            self.object_map.insert(key, self.object_vec.len());
+        }
+
+        self.push_object(name, node);
+    }
+
+    /// Removes the last child node and returns it, along with its name.
+    pub(crate) fn pop_child(&mut self) -> Option<(Arc<str>, Self)> {
+        let (name, node) = self.object_vec.pop()?;
+        let key = self.map_key(&name);
+
+        if self.object_map.get(&*key) == Some(&self.object_vec.len()) {
+            self.object_map.remove(&*key);
+        }
+
+        Some((name, node))
+    }
+
+    /// Recreates `object_map` from `object_vec`. Names shared by multiple siblings point to the
+    /// first of them.
+    pub(crate) fn rebuild_object_map(&mut self) {
+        self.object_map.clear();
+
+        for (index, (name, node)) in self.object_vec.iter().enumerate() {
+            if !node.is_comment && !self.object_map.contains_key(&*self.map_key(name)) {
+                self.object_map.insert(self.shared_key(name, None), index);
+            }
+        }
+    }
+
+    /// Splits a path segment like `name[2]` into its name and index. Segments without a valid
+    /// index refer to the first node with that name.
+    pub(crate) fn parse_path_segment(segment: &str) -> (&str, usize) {
+        segment
+            .strip_suffix(']')
+            .and_then(|segment| segment.rsplit_once('['))
+            .and_then(|(name, index)| Some((name, index.parse().ok()?)))
+            .unwrap_or((segment, 0))
+    }
+
+    /// Returns the `index`th child node with the given name.
+    pub(crate) fn get_nth(&self, name: &str, index: usize) -> Option<&Self> {
+        if index == 0 {
+            return self.try_get(name);
+        }
+
+        self.get_all(name).into_iter().nth(index)
+    }
+
+    /// Returns the `index`th child node with the given name, inserting empty siblings until it
+    /// exists.
+    pub(crate) fn get_nth_mut(&mut self, name: &str, index: usize) -> &mut Self {
+        if index == 0 {
+            return self.get(name);
+        }
+
+        let mut positions: Vec<usize> = self
+            .object_vec
+            .iter()
+            .enumerate()
+            .filter(|(_, (node_name, node))| !node.is_comment && self.names_match(node_name, name))
+            .map(|(position, _)| position)
+            .collect();
+
+        while positions.len() <= index {
+            positions.push(self.object_vec.len());
+            self.push_child(name, self.new_child());
+        }
+
+        &mut self.object_vec[positions[index]].1
+    }
+
+    /// Returns the child nodes along with their path below the given one, their name and their
+    /// index among the siblings sharing their name, as used by `Datafile::get_path`. Comments are
+    /// skipped.
+    pub(crate) fn indexed_children<'a>(
+        &'a self,
+        path: &str,
+    ) -> impl Iterator<Item = (String, &'a str, usize, &'a Self)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{path}.")
+        };
+
+        self.object_vec
+            .iter()
+            .filter(|(_, child)| !child.is_comment)
+            .map(move |(name, child)| {
+                let count = counts.entry(self.map_key(name).into_owned()).or_default();
+                let index = *count;
+                *count += 1;
+
+                let path = if index == 0 {
+                    format!("{prefix}{name}")
+                } else {
+                    format!("{prefix}{name}[{index}]")
+                };
+
+                (path, &**name, index, child)
+            })
+    }
+
+    /// Creates an empty node sharing this node's configuration.
+    pub(crate) fn new_child(&self) -> Self {
+        Self {
+            quote_char: self.quote_char,
+            max_value_count: self.max_value_count,
+            case_insensitive: self.case_insensitive,
+            ..Self::new(Some(self.list_separator), Some(&self.whitespace_sequence))
+        }
+    }
+
+    /// Returns the key under which a child node with the given name is stored in `object_map`.
+    #[inline]
+    pub(crate) fn map_key<'b>(&self, name: &'b str) -> Cow<'b, str> {
+        if self.case_insensitive {
+            Cow::Owned(name.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
+    /// Returns the key under which a child node with the given name is stored in `object_map`,
+    /// sharing the storage of the name unless it has to be lowercased.
+    fn shared_key(&self, name: &Arc<str>, interner: Option<&mut Interner>) -> Arc<str> {
+        match (self.map_key(name), interner) {
+            (Cow::Borrowed(_), _) => Arc::clone(name),
+            (Cow::Owned(key), Some(interner)) => interner.intern(&key),
+            (Cow::Owned(key), None) => Arc::from(key),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn names_match(&self, a: &str, b: &str) -> bool {
+        if self.case_insensitive {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    }
+}
+
+#[cfg(test)]
+impl Datafile {
+    /// Writes the datafile to a string and reads it back with the same configuration, keeping the
+    /// whitespace within quotation marks, panicking with the differences if the result isn't equal to the datafile. Only the children are
+    /// compared, as the writer doesn't write the values of the top-level node. Datafiles that
+    /// `validate` rejects aren't expected to round-trip and panic right away.
+    #[track_caller]
+    pub(crate) fn assert_round_trips(&self) {
+        if let Err(errors) = self.validate() {
+            panic!("datafile isn't supposed to round-trip: {errors:?}");
+        }
+
+        let text = self.write_to_string_with(&WriteOptions::new());
+        let mut result = self.new_child();
+        let mut reader = Reader::new(&mut result);
+        reader.options.trim_values = false;
+        reader.read_bytes(text.as_bytes()).unwrap();
+
+        let mut expected = self.clone();
+        expected.contents.clear();
+        expected.nulls.clear();
+
+        assert!(
+            result == expected,
+            "datafile doesn't round-trip, changes: {:#?}\nwritten as:\n{text}",
+            expected.diff(&result)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::writer::Writer;
+    use crate::test_utils::temp_path;
+
+    fn get_datafile() -> Datafile {
+        Datafile::new(None, None)
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_datafile_gzip() {
+        let mut datafile = Datafile::new(None, None);
+        for index in 0..100 {
+            let entity = datafile.push_array_item("entity");
+            entity.get("name").set_string(&format!("entity_{index}"), 0);
+            entity.get("pos").set_real_list(&[1.5, 2.5]);
+        }
+
+        let path = temp_path("gzip.txt.gz");
+        datafile.write_gz(&path).unwrap();
+
+        let compressed = std::fs::read(&path).unwrap();
+        assert_eq!(compressed[..2], [0x1F, 0x8B]);
+        let text = datafile.write_to_string_with(&WriteOptions::default());
+        assert!(compressed.len() < text.len() / 4);
+
+        let mut result = Datafile::new(None, None);
+        result.read_gz(&path).unwrap();
+        assert_eq!(result, datafile);
+
+        // Uncompressed files aren't read as gzip.
+        datafile.write(&path).unwrap();
+        assert!(result.read_gz(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_datafile_round_trips() {
+        let values = [
+            "plain",
+            "",
+            " padded ",
+            "a, b",
+            "#hash",
+            "{brace}",
+            "}",
+            "key = value",
+            "1.5",
+            "-0",
+            "ünïcödé",
+        ];
+
+        let mut datafile = get_datafile();
+        for (index, value) in values.iter().enumerate() {
+            datafile.get(&format!("value_{index}")).set_string(value, 0);
+        }
+
+        datafile.get("list").set_string_list(&values);
+        datafile.get("parent").set_string_list(&["a", ""]);
+        datafile.get("parent").get("child").set_integer(1, 0);
+        datafile
+            .get("nested")
+            .get("deeper")
+            .get("deepest")
+            .set_bool(true, 0);
+        datafile.get("a=b").set_string("reserved key", 0);
+        datafile.get("{c}").get("#d").set_integer(2, 0);
+        datafile
+            .get_path_mut("documented")
+            .set_doc_comment(" first\n\n second");
+        datafile.get("documented").set_integer(3, 0);
+        datafile.assert_round_trips();
+
+        let mut datafile = Datafile::new(Some(';'), Some("  "));
+        datafile.get("list").set_string_list(&["a, b", "c;d", "e"]);
+        datafile
+            .get("node")
+            .get("child")
+            .set_string_list(&["f", "g"]);
+        datafile.assert_round_trips();
+
+        let mut datafile = get_datafile();
+        datafile.quote_char = '\'';
+        datafile.get("list").set_string_list(&["a, b", "\"c\""]);
+        datafile.assert_round_trips();
+    }
+
+    #[test]
+    fn test_datafile_basic() {
+        let mut datafile = get_datafile();
+        assert_eq!(datafile.get_value_count(), 0);
+
+        datafile.set_string("test", 0);
+        assert_eq!(datafile.get_value_count(), 1);
+        assert_eq!(datafile.get_string(0), "test");
+
+        datafile.set_real(1.5, 1);
+        assert_eq!(datafile.get_value_count(), 2);
+        assert_eq!(datafile.get_real(1), 1.5);
+
+        datafile.set_integer(1, 2);
+        assert_eq!(datafile.get_value_count(), 3);
+        assert_eq!(datafile.get_integer(2), 1);
+    }
+
+    #[test]
+    fn test_datafile_real_precision() {
+        let mut datafile = get_datafile();
+        datafile.set_real_precision(1.88, 0, 2);
+        datafile.set_real_precision(2.0, 1, 2);
+        datafile.set_real_precision(-1.005, 2, 2);
+        datafile.set_real_precision(2.5001, 3, 0);
+        datafile.set_real_precision(-0.4, 4, 0);
+
+        assert_eq!(
+            datafile.values_slice(),
+            ["1.88", "2.00", "-1.00", "3", "-0"]
+        );
+        assert_eq!(datafile.get_real_list(), [1.88, 2.0, -1.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn test_datafile_first_and_last() {
+        let mut datafile = get_datafile();
+        assert_eq!(datafile.first_string(), None);
+        assert_eq!(datafile.last_string(), None);
+        assert_eq!(datafile.first_real(), None);
+        assert_eq!(datafile.last_integer(), None);
+
+        datafile.set_string("", 0);
+        assert_eq!(datafile.first_string(), Some(""));
+        assert_eq!(datafile.last_string(), Some(""));
+        assert_eq!(datafile.first_integer(), Some(0));
+
+        datafile.set_integer(7, 0);
+        assert_eq!(datafile.first_integer(), Some(7));
+        assert_eq!(datafile.last_integer(), Some(7));
+
+        datafile.set_real_list(&[1.5, 2.0, -3.25]);
+        assert_eq!(datafile.first_string(), Some("1.5"));
+        assert_eq!(datafile.last_string(), Some("-3.25"));
+        assert_eq!(datafile.first_real(), Some(1.5));
+        assert_eq!(datafile.last_real(), Some(-3.25));
+        assert_eq!(datafile.first_integer(), Some(1));
+    }
+
+    #[test]
+    fn test_datafile_update_value() {
+        let mut datafile = get_datafile();
+        datafile.set_null(1);
+
+        for _ in 0..3 {
+            datafile.update_value(1, |value| {
+                *value = (value.parse::<i32>().unwrap_or(0) + 1).to_string();
+            });
+        }
+
+        assert_eq!(datafile.get_value_count(), 2);
+        assert_eq!(datafile.get_integer(1), 3);
+        assert!(!datafile.is_null(1));
+
+        datafile.update_value(3, |value| value.push('x'));
+        assert_eq!(datafile.values_slice(), ["", "3", "", "x"]);
+    }
+
+    #[test]
+    fn test_datafile_debug() {
+        let mut datafile = get_datafile();
+        datafile.set_string("top", 0);
+        datafile.get("name").set_string("Javid", 0);
+
+        let mut node = datafile.get("deep");
+        for depth in 0..10 {
+            node = node.get(&format!("level_{depth}"));
+        }
+        node.set_integer(1, 0);
+
+        let wide = datafile.get("wide");
+        for index in 0..40 {
+            wide.get(&format!("item_{index}")).set_integer(index, 0);
+        }
+
+        let text = format!("{datafile:?}");
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(
+            lines[..7],
+            ["= top", "name = Javid", "", "deep", "{", "", "\tlevel_0"]
+        );
+
+        assert!(lines.contains(&"\t\t\t\t\t\t\t\t... (3 nodes)"));
+        assert!(lines.contains(&"\titem_31 = 31"));
+        assert!(!lines.contains(&"\titem_32 = 32"));
+        assert!(lines.contains(&"\t... (8 nodes)"));
+
+        let text = format!("{datafile:#?}");
+        assert!(text.contains("\titem_39 = 39\n"));
+        assert!(!text.contains("..."));
+    }
+
+    #[test]
+    fn test_datafile_object() {
+        let mut datafile = get_datafile();
+
+        let some_node = datafile.get("some_node");
+        some_node.get("name").set_string("Javid", 0);
+        some_node.get("age").set_integer(24, 0);
+        some_node.get("height").set_real(1.88, 0);
+
+        some_node.get("some_list").set_string("item1", 0);
+        some_node.get("some_list").set_string("item2", 1);
+        some_node.get("some_list").set_string("item3", 2);
+
+        let code = some_node.get("code");
+        code.set_string("c++", 0);
+        code.set_string("vhdl", 1);
+        code.set_string("lua", 2);
+
+        let pc = some_node.get("pc");
+        pc.get("processor").set_string("intel", 0);
+        pc.get("ram").set_integer(32, 0);
+
+        // PC
+        assert_eq!(pc.get("processor").get_value_count(), 1);
+        assert_eq!(pc.get("processor").get_string(0), "intel");
+
+        assert_eq!(pc.get("ram").get_value_count(), 1);
+        assert_eq!(pc.get("ram").get_integer(0), 32);
+
+        // Some node
+        assert_eq!(some_node.get("name").get_value_count(), 1);
+        assert_eq!(some_node.get("name").get_string(0), "Javid");
+
+        assert_eq!(some_node.get("age").get_value_count(), 1);
+        assert_eq!(some_node.get("age").get_integer(0), 24);
+
+        assert_eq!(some_node.get("height").get_value_count(), 1);
+        assert_eq!(some_node.get("height").get_real(0), 1.88);
+
+        // Code
+        assert_eq!(some_node.get("code").get_string(0), "c++");
+        assert_eq!(some_node.get("code").get_string(1), "vhdl");
+        assert_eq!(some_node.get("code").get_string(2), "lua");
+    }
+
+    #[test]
+    fn test_datafile_array_items() {
+        let mut datafile = get_datafile();
+        datafile.get("title").set_string("level", 0);
+
+        for (name, health) in [("orc", 100), ("goblin", 50), ("orc", 80)] {
+            let enemy = datafile.push_array_item("enemy");
+            enemy.get("name").set_string(name, 0);
+            enemy.get("health").set_integer(health, 0);
+            enemy.get("position").set_integer_list(&[health / 10, 2]);
+        }
+        datafile
+            .push_array_item("empty_array_item")
+            .set_integer(1, 0);
+        assert_eq!(datafile.array_items("missing").count(), 0);
+
+        let text = datafile.write_to_string_with(&WriteOptions::default());
+        let mut result = get_datafile();
+        result.read_bytes(text.as_bytes()).unwrap();
+        assert_eq!(result, datafile);
+
+        let enemies: Vec<_> = result
+            .array_items("enemy")
+            .map(|enemy| {
+                (
+                    enemy.try_get("name").unwrap().get_string(0),
+                    enemy.try_get("health").unwrap().get_integer(0),
+                )
+            })
+            .collect();
+        assert_eq!(
+            enemies,
+            [
+                ("orc".to_string(), 100),
+                ("goblin".to_string(), 50),
+                ("orc".to_string(), 80)
+            ]
+        );
+
+        assert_eq!(result.get("enemy").get("health").get_integer(0), 100);
+        assert_eq!(
+            result.get_path("enemy[2].position").unwrap().get_integer(0),
+            8
+        );
+    }
+
+    #[test]
+    fn test_datafile_path() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("a").get("b").set_integer(1, 0);
+        datafile.push_array_item("item").set_integer(2, 0);
+        datafile.push_array_item("item").set_integer(3, 0);
+
+        for path in [
+            "a", "a.b", "a.c", "item", "item[1]", "item[2]", "a.b[1]", "",
+        ] {
+            assert_eq!(
+                DatafilePath::new(path).resolve(&datafile),
+                datafile.get_path(path),
+                "{path}"
+            );
+        }
+
+        let path = DatafilePath::new("item[1]");
+        assert_eq!(path, DatafilePath::new("item[1]"));
+        assert_eq!(path.resolve(&datafile).unwrap().get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_datafile_set_raw() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.set_null(3);
+        datafile.set_raw("a, \"b,c\", d");
+
+        assert_eq!(datafile.get_value_count(), 3);
+        assert_eq!(datafile.get_string(0), "a");
+        assert_eq!(datafile.get_string(1), "b,c");
+        assert_eq!(datafile.get_string(2), "d");
+        assert!(!datafile.is_null(3));
+
+        let mut datafile = crate::DatafileBuilder::new()
+            .list_separator(';')
+            .quote_char('\'')
+            .build()
+            .unwrap();
+        datafile.set_raw("1; ' padded ';");
+        assert_eq!(datafile.get_string_list(), ["1", " padded ", ""]);
+    }
+
+    #[test]
+    fn test_datafile_glob() {
+        let mut datafile = Datafile::new(None, None);
+        let world = datafile.get("world");
+        world.get("player").get("health").set_integer(100, 0);
+        world.get("player").get("pos").get("x").set_integer(1, 0);
+        world
+            .push_array_item("enemy")
+            .get("health")
+            .set_integer(20, 0);
+        world
+            .push_array_item("enemy")
+            .get("health")
+            .set_integer(30, 0);
+        datafile.get("health").set_integer(5, 0);
+
+        let paths = |pattern| -> Vec<String> {
+            datafile
+                .glob(pattern)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        };
+
+        assert_eq!(
+            paths("world.*.health"),
+            [
+                "world.player.health",
+                "world.enemy.health",
+                "world.enemy[1].health"
+            ]
+        );
+        assert_eq!(
+            paths("**.health"),
+            [
+                "world.player.health",
+                "world.enemy.health",
+                "world.enemy[1].health",
+                "health"
+            ]
+        );
+        assert_eq!(paths("world.**.x"), ["world.player.pos.x"]);
+        assert_eq!(paths("world.player.**").len(), 4);
+        assert_eq!(paths("**").len(), 10);
+
+        // Literal segments use the notation of `get_path`.
+        assert_eq!(paths("world.enemy[1].health"), ["world.enemy[1].health"]);
+        assert_eq!(paths("world.enemy.health"), ["world.enemy.health"]);
+        assert!(paths("world.*.missing").is_empty());
+
+        let matches = datafile.glob("world.enemy[1].health");
+        assert_eq!(matches[0].1.get_integer(0), 30);
+    }
+
+    #[test]
+    fn test_datafile_hash() {
+        fn hash(datafile: &Datafile) -> u64 {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            datafile.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let build = |separator| {
+            let mut datafile = Datafile::new(Some(separator), None);
+            datafile.get("name").set_string("Javid", 0);
+            datafile.get("pc").get("ram").set_integer(32, 0);
+            datafile
+        };
+
+        let first = build(',');
+        let second = build(';');
+        assert_eq!(first, second);
+        assert_eq!(hash(&first), hash(&second));
+
+        let mut third = build(',');
+        third.get("pc").get("ram").set_integer(64, 0);
+        assert_ne!(hash(&first), hash(&third));
+
+        let cache: std::collections::HashSet<_> = [first, second, third].into_iter().collect();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_datafile_get_or() {
+        let mut datafile = get_datafile();
+        datafile.get("name").set_string("Javid", 0);
+
+        let mut default = get_datafile();
+        default.set_string("unknown", 0);
+
+        assert_eq!(datafile.get_or("name", &default).get_string(0), "Javid");
+        assert_eq!(datafile.get_or("title", &default).get_string(0), "unknown");
+        assert!(!datafile.has_property("title"));
+    }
+
+    #[test]
+    fn test_datafile_get_all() {
+        let mut datafile = get_datafile();
+        assert!(datafile.get_all("enemy").is_empty());
+
+        let mut first = get_datafile();
+        first.set_string("orc", 0);
+        let mut second = get_datafile();
+        second.set_string("goblin", 0);
+
+        datafile.push_child("enemy", first);
+        datafile.push_child("enemy", second);
+        datafile.get("other");
+
+        let enemies = datafile.get_all("enemy");
+        assert_eq!(enemies.len(), 2);
+        assert_eq!(enemies[0].get_string(0), "orc");
+        assert_eq!(enemies[1].get_string(0), "goblin");
+
+        // Lookups by name resolve to the first sibling.
+        assert_eq!(datafile.get("enemy").get_string(0), "orc");
+    }
+
+    #[test]
+    fn test_datafile_visit() {
+        let mut datafile = get_datafile();
+        let some_node = datafile.get("some_node");
+        some_node.get("name").set_string("Javid", 0);
+        some_node.get("pc").get("ram").set_integer(32, 0);
+        datafile.get("other");
+
+        let mut paths = vec![];
+        datafile.visit(|path, _| paths.push(path.join(".")));
+
+        assert_eq!(
+            paths,
+            [
+                "some_node",
+                "some_node.name",
+                "some_node.pc",
+                "some_node.pc.ram",
+                "other"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_datafile_visit_mut() {
+        let mut datafile = get_datafile();
+        datafile.get("a").get("b").set_string("  padded  ", 0);
+        datafile.get("c").set_string(" x", 0);
+
+        datafile.visit_mut(|_, node| {
+            for index in 0..node.get_value_count() {
+                let value = node.get_string(index);
+                node.set_string(value.trim(), index);
+            }
+        });
+
+        assert_eq!(datafile.get("a").get("b").get_string(0), "padded");
+        assert_eq!(datafile.get("c").get_string(0), "x");
+    }
+
+    #[test]
+    fn test_datafile_case_sensitive() {
+        let mut datafile = get_datafile();
+        datafile.get("Name").set_string("Javid", 0);
+
+        assert!(datafile.has_property("Name"));
+        assert!(!datafile.has_property("name"));
+        assert_eq!(datafile.get("name").get_string(0), "");
+        assert_eq!(datafile.get_all("Name").len(), 1);
+    }
+
+    #[test]
+    fn test_datafile_case_insensitive() {
+        let mut datafile = get_datafile();
+        datafile.set_case_insensitive(true);
+        datafile.get("Name").set_string("Javid", 0);
+        datafile.get("pc").get("RAM").set_integer(32, 0);
+
+        assert!(datafile.has_property("name"));
+        assert_eq!(datafile.get("NAME").get_string(0), "Javid");
+        assert_eq!(datafile.get("PC").get("ram").get_integer(0), 32);
+        assert_eq!(datafile.get_all("name").len(), 1);
+
+        // The original casing is preserved.
+        assert_eq!(&*datafile.object_vec[0].0, "Name");
+        assert_eq!(datafile.object_vec.len(), 2);
+    }
+
+    #[test]
+    fn test_datafile_case_insensitive_tie_breaking() {
+        let mut datafile = get_datafile();
+        datafile.get("Name").set_string("first", 0);
+        datafile.get("name").set_string("second", 0);
+
+        datafile.set_case_insensitive(true);
+        assert_eq!(datafile.get("name").get_string(0), "first");
+        assert_eq!(datafile.get_all("NAME").len(), 2);
+
+        datafile.set_case_insensitive(false);
+        assert_eq!(datafile.get("name").get_string(0), "second");
+    }
+
+    #[test]
+    fn test_datafile_rename() {
+        let mut datafile = get_datafile();
+        datafile.get("first").set_integer(1, 0);
+        datafile.get("old").get("child").set_integer(2, 0);
+        datafile.get("last").set_integer(3, 0);
+
+        assert!(datafile.rename("old", "new"));
+        assert!(!datafile.has_property("old"));
+        assert!(datafile.has_property("new"));
+        assert_eq!(datafile.get("new").get("child").get_integer(0), 2);
+
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            "first = 1\n\nnew\n{\n\tchild = 2\n}\nlast = 3\n"
+        );
+
+        assert!(!datafile.rename("missing", "other"));
+        assert!(!datafile.rename("first", "last"));
+        assert_eq!(datafile.get("first").get_integer(0), 1);
+        assert_eq!(datafile.get("last").get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_datafile_rename_duplicate() {
+        let mut datafile = get_datafile();
+        let mut first = get_datafile();
+        first.set_string("orc", 0);
+        let mut second = get_datafile();
+        second.set_string("goblin", 0);
+        datafile.push_child("enemy", first);
+        datafile.push_child("enemy", second);
+
+        assert!(datafile.rename("enemy", "boss"));
+        assert_eq!(datafile.get("boss").get_string(0), "orc");
+        assert_eq!(datafile.get("enemy").get_string(0), "goblin");
+    }
+
+    #[test]
+    fn test_datafile_take_and_insert() {
+        let mut datafile = get_datafile();
+        let source = datafile.get("source");
+        source.get("first").set_integer(1, 0);
+        source.get("branch").get("leaf").set_string("value", 0);
+        source.get("last").set_integer(2, 0);
+        datafile.get("target").get("existing").set_integer(3, 0);
+
+        let branch = datafile.get("source").take("branch").unwrap();
+        assert!(datafile.get("source").take("branch").is_none());
+        datafile.get("target").insert("branch", branch);
+
+        let source = datafile.get("source");
+        assert!(!source.has_property("branch"));
+        assert_eq!(source.get("last").get_integer(0), 2);
+        assert_eq!(source.child_count(), 2);
+
+        let target = datafile.get("target");
+        assert_eq!(target.get("existing").get_integer(0), 3);
+        assert_eq!(target.get("branch").get("leaf").get_string(0), "value");
+        assert_eq!(target.child_count(), 2);
+
+        // Inserting under an existing name replaces the node in place.
+        let mut replacement = get_datafile();
+        replacement.set_integer(4, 0);
+        target.insert("existing", replacement);
+        assert_eq!(target.get("existing").get_integer(0), 4);
+        assert_eq!(&*target.object_vec[0].0, "existing");
+        assert_eq!(target.child_count(), 2);
+    }
+
+    #[test]
+    fn test_datafile_try_insert() {
+        let mut datafile = get_datafile();
+        datafile.set_case_insensitive(true);
+
+        let mut node = get_datafile();
+        node.get("child").set_integer(1, 0);
+        assert_eq!(datafile.try_insert("Node", node.clone()), Ok(()));
+
+        let mut conflicting = get_datafile();
+        conflicting.set_integer(2, 0);
+        assert_eq!(
+            datafile.try_insert("node", conflicting.clone()),
+            Err(conflicting.clone())
+        );
+        assert_eq!(datafile.get("node"), &node);
+
+        datafile.insert("node", conflicting.clone());
+        assert_eq!(datafile.get("NODE"), &conflicting);
+        assert_eq!(datafile.child_count(), 1);
+    }
+
+    #[test]
+    fn test_datafile_total_counts() {
+        let mut datafile = get_datafile();
+        assert_eq!(datafile.total_node_count(), 0);
+        assert_eq!(datafile.total_value_count(), 0);
+
+        let some_node = datafile.get("some_node");
+        some_node.get("name").set_string("Javid", 0);
+        let code = some_node.get("code");
+        code.set_string("c++", 0);
+        code.set_string("vhdl", 1);
+        code.set_string("lua", 2);
+        some_node.get("pc").get("ram").set_integer(32, 0);
+        datafile.push_object(
+            "# comment",
+            Datafile {
+                is_comment: true,
+                ..get_datafile()
+            },
+        );
+
+        // some_node, name, code, pc and ram
+        assert_eq!(datafile.total_node_count(), 5);
+        assert_eq!(datafile.total_value_count(), 5);
+        assert_eq!(datafile.get_value_count(), 0);
+        assert_eq!(datafile.get("some_node").total_node_count(), 4);
+    }
+
+    #[test]
+    fn test_datafile_into_iter() {
+        let mut datafile = get_datafile();
+        datafile.get("a").set_integer(1, 0);
+        datafile.push_object(
+            "# comment",
+            Datafile {
+                is_comment: true,
+                ..get_datafile()
+            },
+        );
+        datafile.get("b").get("c").set_integer(2, 0);
+
+        let names: Vec<_> = (&datafile).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["a", "b"]);
+
+        let mut map = HashMap::new();
+        for (name, node) in datafile.clone() {
+            map.insert(name, node);
+        }
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"].get_integer(0), 1);
+        assert_eq!(map["b"], *datafile.get("b"));
+    }
 
-    /// Does the same as `get_property`, but writes it out in array notation.   
-    pub fn get_indexed_property(&mut self, name: &str, index: usize) -> &Self {
-        self.get_property(&format!("{}[{}]", name, index))
+    #[test]
+    fn test_datafile_entries() {
+        let mut datafile = get_datafile();
+        datafile
+            .read_bytes(b"# about\n\nname = Javid\npc\n{\n\tram = 32\n}\ncode = c++, lua\n")
+            .unwrap();
+
+        let entries: Vec<_> = datafile.entries().collect();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(entries[0], Entry::Comment("# about")));
+        assert!(matches!(
+            entries[1],
+            Entry::Value { key: "name", values: [name] } if name == "Javid"
+        ));
+        assert!(matches!(entries[2], Entry::Node("pc", node) if node.child_count() == 1));
+        assert!(matches!(
+            entries[3],
+            Entry::Value { key: "code", values: [a, b] } if a == "c++" && b == "lua"
+        ));
     }
 
-    #[inline]
-    pub(crate) fn push_object(&mut self, name: &str, object: Self) {
-        self.object_vec.push((name.to_string(), object));
+    #[test]
+    fn test_datafile_child_count() {
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["a", "b", "c"]);
+        datafile.get("first").set_integer(1, 0);
+        datafile.get("second").get("nested").set_integer(2, 0);
+        datafile.push_object(
+            "# comment",
+            Datafile {
+                is_comment: true,
+                ..get_datafile()
+            },
+        );
+
+        assert_eq!(datafile.get_value_count(), 3);
+        assert_eq!(datafile.child_count(), 2);
+        assert_eq!(datafile.comment_count(), 1);
+        assert_eq!(datafile.get("first").get_value_count(), 1);
+        assert_eq!(datafile.get("first").child_count(), 0);
+        assert_eq!(datafile.get("second").get_value_count(), 0);
+        assert_eq!(datafile.get("second").child_count(), 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_datafile_leaf_and_empty() {
+        let mut datafile = get_datafile();
+        datafile.get("value").set_integer(1, 0);
+        datafile.get("container").get("child").set_integer(2, 0);
+        datafile.get("empty");
 
-    fn get_datafile() -> Datafile {
-        Datafile::new(None, None)
+        assert!(datafile.get("value").is_leaf());
+        assert!(!datafile.get("value").is_empty());
+        assert!(!datafile.get("container").is_leaf());
+        assert!(!datafile.get("container").is_empty());
+        assert!(datafile.get("empty").is_leaf());
+        assert!(datafile.get("empty").is_empty());
     }
 
     #[test]
-    fn test_datafile_basic() {
+    fn test_datafile_normalize() {
+        let mut datafile = get_datafile();
+        datafile.get("empty");
+        datafile.get("nested").get("a").get("b");
+        datafile.get("kept").get("empty");
+        datafile.get("kept").get("value").set_integer(1, 0);
+        let comment = Datafile {
+            is_comment: true,
+            ..get_datafile()
+        };
+        datafile
+            .get("comments")
+            .push_object("# only comments", comment.clone());
+        datafile.push_object("# top-level comment", comment);
+
+        let mut normalized = datafile.clone();
+        normalized.normalize(true);
+        assert!(!normalized.has_property("empty"));
+        assert!(!normalized.has_property("nested"));
+        assert!(!normalized.has_property("comments"));
+        assert!(!normalized.get("kept").has_property("empty"));
+        assert_eq!(normalized.get("kept").get("value").get_integer(0), 1);
+        assert_eq!(normalized.comment_count(), 1);
+
+        datafile.normalize(false);
+        assert_eq!(datafile.child_count(), 1);
+        assert_eq!(datafile.comment_count(), 0);
+    }
+
+    #[test]
+    fn test_datafile_replace_values() {
+        let mut datafile = get_datafile();
+        datafile.set_string("old", 0);
+        datafile.get("a").set_string_list(&["old", "older", "old"]);
+        datafile.get("a").get("b").get("c").set_string("old", 1);
+        datafile.get("d").set_string("new", 0);
+
+        datafile.replace_values(|value| (value == "old").then(|| "new".to_string()));
+
+        assert_eq!(datafile.get_string(0), "new");
+        assert_eq!(datafile.get("a").get_string_list(), ["new", "older", "new"]);
+        assert_eq!(
+            datafile.get("a").get("b").get("c").get_string_list(),
+            ["", "new"]
+        );
+        assert_eq!(datafile.get("d").get_string(0), "new");
+    }
+
+    #[test]
+    fn test_datafile_write_subtree() {
         let mut datafile = get_datafile();
+        datafile.get("world").get("seed").set_integer(42, 0);
+        let player = datafile.get("player");
+        player.set_string("Javid", 0);
+        player.get("health").set_integer(100, 0);
+        player.get("inventory").get("sword").set_integer(1, 0);
+        datafile.get("version").set_integer(2, 0);
+
+        assert_eq!(
+            datafile.write_subtree_to_string("player").unwrap(),
+            "player = Javid\n{\n\thealth = 100\n\n\tinventory\n\t{\n\t\tsword = 1\n\t}\n}\n"
+        );
+        assert_eq!(
+            datafile.write_subtree_to_string("version").unwrap(),
+            "version = 2\n"
+        );
+        assert_eq!(datafile.write_subtree_to_string("missing"), None);
+
+        let path = temp_path("subtree.txt");
+        datafile.write_subtree("player", &path).unwrap();
+        let mut result = get_datafile();
+        result.read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.child_count(), 1);
+        assert_eq!(result.get("player"), datafile.get("player"));
+
+        let error = datafile.write_subtree("missing", &path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_datafile_value_string() {
+        let mut datafile = get_datafile();
+        let node = datafile.get("node");
+        node.set_string_list(&["a", " padded ", "b, c", "#d"]);
+        datafile.get("count").set_integer(12_345, 0);
+        datafile.get("empty");
+
+        let path = temp_path("value_string.txt");
+        datafile.write(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<_> = ["node", "count", "empty"]
+            .iter()
+            .map(|name| format!("{name} = {}", datafile.get(name).value_string()))
+            .collect();
+        assert_eq!(text.lines().collect::<Vec<_>>(), lines);
+
+        let options = WriteOptions {
+            digit_grouping: Some('_'),
+            space_after_separator: false,
+            inline_comments: true,
+            ..WriteOptions::default()
+        };
+        assert_eq!(
+            datafile.get("count").value_string_with(&options),
+            "\"12_345\""
+        );
+        assert_eq!(
+            datafile.get("node").value_string_with(&options),
+            "a,\" padded \",\"b, c\",\"#d\""
+        );
+    }
+
+    #[test]
+    fn test_datafile_clear() {
+        let mut datafile = Datafile::new(Some(';'), Some("  "));
+        datafile.set_case_insensitive(true);
+        datafile.set_string("value", 0);
+        datafile.get("node").get("child").set_integer(1, 0);
+
+        datafile.clear();
+
         assert_eq!(datafile.get_value_count(), 0);
+        assert_eq!(datafile.total_node_count(), 0);
+        assert!(!datafile.has_property("node"));
+        assert_eq!(datafile.list_separator, ';');
+        assert_eq!(datafile.whitespace_sequence, "  ");
+        assert!(datafile.is_case_insensitive());
+    }
 
-        datafile.set_string("test", 0);
-        assert_eq!(datafile.get_value_count(), 1);
-        assert_eq!(datafile.get_string(0), "test");
+    #[test]
+    fn test_datafile_shrink_to_fit() {
+        let mut datafile = get_datafile();
+        datafile.reserve(4_000);
 
-        datafile.set_real(1.5, 1);
-        assert_eq!(datafile.get_value_count(), 2);
-        assert_eq!(datafile.get_real(1), 1.5);
+        for index in 0..1_000 {
+            let node = datafile.get(&format!("node_{index}"));
+            node.set_integer_list(&[1, 2, 3, 4, 5, 6, 7, 8]);
+            node.get("child").set_string("value", 0);
+            node.clear();
+        }
 
-        datafile.set_integer(1, 2);
-        assert_eq!(datafile.get_value_count(), 3);
-        assert_eq!(datafile.get_integer(2), 1);
+        let capacities = |datafile: &Datafile| -> Vec<_> {
+            datafile
+                .object_vec
+                .iter()
+                .map(|(_, node)| (node.contents.capacity(), node.object_vec.capacity()))
+                .collect()
+        };
+
+        let before = datafile.object_vec.capacity();
+        let children_before = capacities(&datafile);
+        assert!(before >= 4_000);
+        assert!(children_before
+            .iter()
+            .all(|&(contents, children)| contents > 0 && children > 0));
+
+        datafile.shrink_to_fit();
+
+        // Shrinking only guarantees a capacity of at least the length, not an exact one.
+        let capacity = datafile.object_vec.capacity();
+        assert!(capacity < before && capacity >= datafile.object_vec.len());
+        for (after, before) in capacities(&datafile).iter().zip(&children_before) {
+            assert!(after.0 < before.0);
+            assert!(after.1 < before.1);
+        }
     }
 
     #[test]
-    fn test_datafile_object() {
+    fn test_datafile_get_property() {
+        let mut datafile = get_datafile();
+        let a = datafile.get("a");
+        a.get("b").get("c").set_integer(1, 0);
+
+        for value in 2..=3 {
+            let mut sibling = get_datafile();
+            sibling.get("c").set_integer(value, 0);
+            a.push_child("b", sibling);
+        }
+
+        assert_eq!(datafile.get_property("a.b.c").get_integer(0), 1);
+        assert_eq!(datafile.get_property("a.b[0].c").get_integer(0), 1);
+        assert_eq!(datafile.get_property("a.b[2].c").get_integer(0), 3);
+        let b = datafile.get_indexed_property("a.b", 1);
+        assert_eq!(b.try_get("c").unwrap().get_integer(0), 2);
+
+        // Missing nodes along the path are inserted.
+        assert_eq!(datafile.get_property("x.y.z").get_value_count(), 0);
+        assert!(datafile.get_path("x.y.z").is_some());
+        assert_eq!(datafile.get_property("a.b[4]").get_value_count(), 0);
+        assert_eq!(datafile.get_all("a")[0].get_all("b").len(), 5);
+    }
+
+    #[test]
+    fn test_datafile_get_path() {
+        let mut datafile = get_datafile();
+        datafile.get("a").get("b").set_integer(1, 0);
+        datafile.get("a").push_child("b", get_datafile());
+
+        assert!(datafile.get_path("a").is_some());
+        assert!(datafile.get_path("a.b[1]").is_some());
+        assert!(datafile.get_path("a.b[2]").is_none());
+        assert!(datafile.get_path("a.c").is_none());
+        assert!(datafile.get_path("missing.b").is_none());
+        assert_eq!(datafile.get_path("a.b").unwrap().get_integer(0), 1);
+        assert_eq!(datafile.total_node_count(), 3);
+    }
+
+    #[test]
+    fn test_datafile_contains_path() {
         let mut datafile = get_datafile();
+        datafile.get("a").get("b").get("c").set_integer(1, 0);
+        datafile.get("a").push_child("b", get_datafile());
+
+        assert!(datafile.contains_path("a"));
+        assert!(datafile.contains_path("a.b.c"));
+        assert!(datafile.contains_path("a.b[1]"));
+        assert!(!datafile.contains_path("a.b[1].c"));
+        assert!(!datafile.contains_path("a.x.c"));
+        assert!(!datafile.contains_path("a.b.c.d"));
+        assert!(!datafile.contains_path(""));
+        assert_eq!(datafile.total_node_count(), 4);
+    }
 
+    #[test]
+    fn test_datafile_flatten() {
+        let mut datafile = get_datafile();
         let some_node = datafile.get("some_node");
         some_node.get("name").set_string("Javid", 0);
         some_node.get("age").set_integer(24, 0);
         some_node.get("height").set_real(1.88, 0);
-
-        some_node.get("some_list").set_string("item1", 0);
-        some_node.get("some_list").set_string("item2", 1);
-        some_node.get("some_list").set_string("item3", 2);
-
-        let code = some_node.get("code");
-        code.set_string("c++", 0);
-        code.set_string("vhdl", 1);
-        code.set_string("lua", 2);
-
+        some_node
+            .get("code")
+            .set_string_list(&["c++", "vhdl", "lua"]);
         let pc = some_node.get("pc");
         pc.get("processor").set_string("intel", 0);
         pc.get("ram").set_integer(32, 0);
+        datafile
+            .get_path_mut("enemy[1].name")
+            .set_string("goblin", 0);
 
-        // PC
-        assert_eq!(pc.get("processor").get_value_count(), 1);
-        assert_eq!(pc.get("processor").get_string(0), "intel");
+        let flat = datafile.flatten();
+        let get = |path: &str| {
+            flat.iter()
+                .find(|(flat_path, _)| flat_path == path)
+                .map(|(_, values)| values.clone())
+        };
 
-        assert_eq!(pc.get("ram").get_value_count(), 1);
-        assert_eq!(pc.get("ram").get_integer(0), 32);
+        assert_eq!(flat.len(), 8);
+        assert_eq!(get("some_node.name").unwrap(), ["Javid"]);
+        assert_eq!(get("some_node.code").unwrap(), ["c++", "vhdl", "lua"]);
+        assert_eq!(get("some_node.pc.ram").unwrap(), ["32"]);
+        assert_eq!(get("enemy").unwrap(), Vec::<String>::new());
+        assert_eq!(get("enemy[1].name").unwrap(), ["goblin"]);
+        assert_eq!(get("some_node"), None);
+        assert_eq!(get("some_node.pc"), None);
+    }
 
-        // Some node
-        assert_eq!(some_node.get("name").get_value_count(), 1);
-        assert_eq!(some_node.get("name").get_string(0), "Javid");
+    #[test]
+    fn test_datafile_string_map() {
+        let map = HashMap::from([
+            ("width".to_string(), "1920".to_string()),
+            ("title".to_string(), "a, b".to_string()),
+            ("empty".to_string(), String::new()),
+        ]);
 
-        assert_eq!(some_node.get("age").get_value_count(), 1);
-        assert_eq!(some_node.get("age").get_integer(0), 24);
+        let mut datafile = Datafile::from(map.clone());
+        let names: Vec<_> = datafile
+            .object_vec
+            .iter()
+            .map(|(name, _)| &**name)
+            .collect();
+        assert_eq!(names, ["empty", "title", "width"]);
+        assert_eq!(datafile.get("width").get_integer(0), 1920);
+        assert_eq!(datafile.get("title").get_value_count(), 1);
+        assert_eq!(datafile.to_string_map(), map);
 
-        assert_eq!(some_node.get("height").get_value_count(), 1);
-        assert_eq!(some_node.get("height").get_real(0), 1.88);
+        datafile.get("list").set_string_list(&["a", "b"]);
+        datafile.get("node").get("child").set_string("c", 0);
+        datafile.get("parent").set_string("d", 0);
+        datafile.get("parent").get("child").set_string("e", 0);
+        datafile.get("none");
+        datafile.push_child("width", {
+            let mut duplicate = get_datafile();
+            duplicate.set_string("800", 0);
+            duplicate
+        });
 
-        // Code
-        assert_eq!(some_node.get("code").get_string(0), "c++");
-        assert_eq!(some_node.get("code").get_string(1), "vhdl");
-        assert_eq!(some_node.get("code").get_string(2), "lua");
+        assert_eq!(datafile.to_string_map(), map);
+    }
+
+    #[test]
+    fn test_datafile_from_flat() {
+        let mut datafile = get_datafile();
+        datafile
+            .read_bytes(
+                b"name = Javid\npc\n{\n\tram = 32\n\tgpu\n\t{\n\t\tvram = 8\n\t}\n}\n\
+                  enemy\n{\n\tname = orc\n}\nenemy\n{\n\tname = goblin\n}\n\
+                  mixed = a, b\n{\n\tchild = 1\n}\nempty =\ncode = c++, lua\n",
+            )
+            .unwrap();
+        datafile.get("empty");
+
+        let rebuilt = Datafile::from_flat(&datafile.flatten());
+        assert_eq!(rebuilt, datafile);
+        assert_eq!(rebuilt.flatten(), datafile.flatten());
+
+        datafile.get("name").set_string("Jx9", 0);
+        assert_ne!(rebuilt, datafile);
+    }
+
+    #[test]
+    fn test_datafile_get_path_mut() {
+        let mut datafile = get_datafile();
+        datafile
+            .get_path_mut("config.video.resolution")
+            .set_string_list(&["1920", "1080"]);
+        datafile
+            .get_path_mut("config.video.fullscreen")
+            .set_bool(true, 0);
+
+        let video = datafile.get_path("config.video").unwrap();
+        assert_eq!(video.child_count(), 2);
+        assert_eq!(video.try_get("resolution").unwrap().get_integer(0), 1920);
+        assert!(video.try_get("fullscreen").unwrap().get_bool(0));
+
+        // Array segments work like they do for reading, inserting siblings where needed.
+        datafile
+            .get_path_mut("items.item[2].name")
+            .set_string("sword", 0);
+        assert_eq!(datafile.get("items").get_all("item").len(), 3);
+        assert_eq!(
+            datafile
+                .get_path("items.item[2].name")
+                .unwrap()
+                .get_string(0),
+            "sword"
+        );
+        assert!(datafile.get_path("items.item[1].name").is_none());
+    }
+
+    #[test]
+    fn test_datafile_lists() {
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["a", "b", "c"]);
+        assert_eq!(datafile.get_string_list(), ["a", "b", "c"]);
+
+        // Previous values are replaced, not merged.
+        datafile.set_string_list(&["d"]);
+        assert_eq!(datafile.get_value_count(), 1);
+        assert_eq!(datafile.get_string_list(), ["d"]);
+
+        datafile.set_integer_list(&[1, 2]);
+        assert_eq!(datafile.get_string_list(), ["1", "2"]);
+        assert_eq!(datafile.get_integer_list(), [1, 2]);
+
+        datafile.set_real_list(&[1.5]);
+        assert_eq!(datafile.get_value_count(), 1);
+        assert_eq!(datafile.get_real_list(), [1.5]);
+
+        datafile.set_string_list(&[]);
+        assert!(datafile.get_string_list().is_empty());
+    }
+
+    #[test]
+    fn test_datafile_numeric_list() {
+        let mut datafile = get_datafile();
+        assert!(datafile.is_numeric_list());
+        assert_eq!(datafile.as_real_vec(), Some(vec![]));
+
+        datafile.set_string_list(&["1", "2.5", "", "-4,25"]);
+        assert!(datafile.is_numeric_list());
+        assert_eq!(datafile.as_real_vec(), Some(vec![1.0, 2.5, 0.0, -4.25]));
+
+        datafile.set_string("abc", 4);
+        assert!(!datafile.is_numeric_list());
+        assert_eq!(datafile.as_real_vec(), None);
+    }
+
+    #[test]
+    fn test_datafile_contains_value() {
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["c++", "vhdl", "lua", "vhdl"]);
+
+        assert!(datafile.contains_value("lua"));
+        assert!(!datafile.contains_value("rust"));
+        assert_eq!(datafile.index_of_value("c++"), Some(0));
+        assert_eq!(datafile.index_of_value("vhdl"), Some(1));
+        assert_eq!(datafile.index_of_value("rust"), None);
+        assert!(!get_datafile().contains_value(""));
+    }
+
+    #[test]
+    fn test_datafile_values_slice() {
+        let mut datafile = get_datafile();
+        assert!(datafile.values_slice().is_empty());
+
+        datafile.set_string("c++", 0);
+        datafile.set_integer(24, 1);
+        datafile.set_string("  padded  ", 3);
+
+        let values = datafile.values_slice();
+        assert_eq!(values.len(), datafile.get_value_count());
+
+        for (index, value) in values.iter().enumerate() {
+            assert_eq!(*value, datafile.get_string(index));
+        }
+    }
+
+    #[test]
+    fn test_datafile_values_matching() {
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["map.png", "song.ogg", "tiles.PNG", "readme", "icon.png"]);
+        let is_png = |value: &str| value.to_ascii_lowercase().ends_with(".png");
+
+        assert_eq!(
+            datafile.values_matching(is_png),
+            ["map.png", "tiles.PNG", "icon.png"]
+        );
+        assert_eq!(datafile.count_values_matching(is_png), 3);
+        assert_eq!(
+            datafile.count_values_matching(|value| value.contains('.')),
+            4
+        );
+        assert!(datafile
+            .values_matching(|value| value.is_empty())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_datafile_entry() {
+        let mut datafile = get_datafile();
+        datafile.entry("a").set_integer(1, 0);
+        datafile.entry("a").set_integer(2, 1);
+
+        assert_eq!(datafile.object_vec.len(), 1);
+        assert_eq!(datafile.get("a").get_integer_list(), [1, 2]);
+    }
+
+    #[test]
+    fn test_datafile_load_save() {
+        let path = temp_path("load_save.txt");
+        let mut datafile = get_datafile();
+        datafile.get("some_node").get("name").set_string("Javid", 0);
+        datafile.get("some_node").get("age").set_integer(24, 0);
+
+        datafile.save(&path).unwrap();
+        let mut loaded = Datafile::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.get_property("some_node.name").get_string(0), "Javid");
+        assert_eq!(loaded.get_property("some_node.age").get_integer(0), 24);
+        assert!(Datafile::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_datafile_read_with() {
+        let path = temp_path("read_with.txt");
+        std::fs::write(
+            &path,
+            b"name = caf\xE9 # lossy\nnode\n{\n\ttext = \"a\nb\"\n}\n",
+        )
+        .unwrap();
+
+        let options = ReadOptions {
+            strict_indentation: true,
+            record_source_lines: true,
+            inline_comments: true,
+            lossy: true,
+            multiline_values: true,
+            ..ReadOptions::default()
+        };
+
+        let mut datafile = get_datafile();
+        assert!(datafile.read(&path).is_err());
+        datafile.read_with(&path, &options).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(datafile.get("name").get_string(0), "caf\u{FFFD}");
+        assert_eq!(datafile.get_property("node.text").get_string(0), "a\nb");
+        assert_eq!(datafile.get("node").source_line(), Some(2));
+    }
+
+    #[test]
+    fn test_datafile_path_types() {
+        let path = temp_path("path_types.txt");
+        let mut datafile = get_datafile();
+        datafile.get("name").set_string("Javid", 0);
+
+        // Owned and borrowed paths, as well as plain strings, are accepted.
+        datafile.write(path.clone()).unwrap();
+        let mut loaded = Datafile::load(path.as_path()).unwrap();
+        assert_eq!(loaded.get("name").get_string(0), "Javid");
+
+        loaded.read(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get("name").get_string(0), "Javid");
+    }
+
+    #[test]
+    fn test_datafile_read_bytes() {
+        let mut datafile = get_datafile();
+        datafile
+            .read_bytes(b"some_node\n{\n\tname = Javid\n\tage = 24\n}\n")
+            .unwrap();
+
+        assert_eq!(
+            datafile.get_property("some_node.name").get_string(0),
+            "Javid"
+        );
+        assert_eq!(datafile.get_property("some_node.age").get_integer(0), 24);
+
+        assert!(datafile.read_bytes(b"name = \xFF\n").is_err());
+        datafile.read_bytes_lossy(b"name = \xFF\n").unwrap();
+        assert_eq!(datafile.get("name").get_string(0), "\u{FFFD}");
+    }
+
+    #[test]
+    fn test_datafile_validate() {
+        let mut datafile = get_datafile();
+        datafile.get("valid").set_string("a=b, c", 0);
+        datafile.get("node").get("child").set_integer(1, 0);
+        assert_eq!(datafile.validate(), Ok(()));
+
+        datafile.get(" padded").set_integer(1, 0);
+        datafile.get("node").get("quote").set_string("fine", 0);
+        datafile.get("node").get("quote").set_string("\"no\"", 1);
+        datafile.get("node").get("lines").set_string("a\nb", 0);
+        datafile.get("node").get("empty");
+
+        assert_eq!(
+            datafile.validate(),
+            Err(vec![
+                DatafileError::InvalidValue {
+                    path: "node.quote".to_string(),
+                    index: 1
+                },
+                DatafileError::InvalidValue {
+                    path: "node.lines".to_string(),
+                    index: 0
+                },
+                DatafileError::EmptyNode("node.empty".to_string()),
+                DatafileError::InvalidKey(" padded".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_datafile_clone_shallow() {
+        let mut datafile = Datafile::new(Some(';'), Some("  "));
+        datafile.set_string_list(&["a", "b"]);
+        datafile.get("child").get("grandchild").set_integer(1, 0);
+
+        let mut shallow = datafile.clone_shallow();
+        assert_eq!(shallow.get_string_list(), ["a", "b"]);
+        assert_eq!(shallow.list_separator, ';');
+        assert_eq!(shallow.whitespace_sequence, "  ");
+        assert_eq!(shallow.total_node_count(), 0);
+        assert!(!shallow.has_property("child"));
+
+        // The shallow clone is independent of the original.
+        shallow.get("other");
+        assert!(!datafile.has_property("other"));
+        assert_eq!(datafile.clone().total_node_count(), 2);
+    }
+
+    #[test]
+    fn test_datafile_generic_values() {
+        let mut datafile = get_datafile();
+        datafile.set_value(24, 0);
+        datafile.set_value(1.5, 1);
+        datafile.set_value("Javid".to_string(), 2);
+        datafile.set_value("c++", 3);
+
+        assert_eq!(datafile.get_value::<i32>(0), 24);
+        assert_eq!(datafile.get_value::<f32>(1), 1.5);
+        assert_eq!(datafile.get_value::<String>(2), "Javid");
+        assert_eq!(datafile.get_value::<&str>(3), "c++");
+
+        // Coercion matches the typed getters.
+        assert_eq!(datafile.get_value::<i32>(1), datafile.get_integer(1));
+        assert_eq!(datafile.get_value::<i32>(10), 0);
+        assert_eq!(datafile.get_value::<&str>(10), "");
+    }
+
+    #[test]
+    fn test_datafile_try_set_string() {
+        let mut datafile = get_datafile();
+        datafile.try_set_string("a", 0).unwrap();
+        datafile.try_set_string("b", 10).unwrap();
+        assert_eq!(datafile.get_value_count(), 11);
+
+        assert_eq!(
+            datafile.try_set_string("huge", usize::MAX - 1),
+            Err(DatafileError::IndexOutOfRange {
+                index: usize::MAX - 1,
+                max_value_count: 1 << 16
+            })
+        );
+        assert_eq!(datafile.get_value_count(), 11);
+
+        // The bound is inherited by child nodes.
+        datafile.max_value_count = 2;
+        assert!(datafile.get("child").try_set_string("c", 2).is_err());
+        assert!(datafile.get("child").try_set_string("c", 1).is_ok());
+    }
+
+    #[test]
+    fn test_datafile_unsigned() {
+        let mut datafile = get_datafile();
+        datafile.set_unsigned(42, 0);
+        datafile.set_string("-3", 1);
+        datafile.set_string("1.9", 2);
+
+        assert_eq!(datafile.get_unsigned(0), 42);
+        assert_eq!(datafile.get_unsigned(1), 0);
+        assert_eq!(datafile.get_unsigned(2), 1);
+        assert_eq!(datafile.get_value::<usize>(0), 42);
+    }
+
+    #[test]
+    fn test_datafile_clamped() {
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["-5", "0.25", "7.5"]);
+
+        assert_eq!(datafile.get_real_clamped(0, 0.0, 1.0), 0.0);
+        assert_eq!(datafile.get_real_clamped(1, 0.0, 1.0), 0.25);
+        assert_eq!(datafile.get_real_clamped(2, 0.0, 1.0), 1.0);
+        assert_eq!(datafile.get_real_clamped(3, 0.5, 1.0), 0.5);
+
+        assert_eq!(datafile.get_integer_clamped(0, 1, 10), 1);
+        assert_eq!(datafile.get_integer_clamped(2, 1, 10), 7);
+        assert_eq!(datafile.get_integer_clamped(2, 1, 5), 5);
+        assert_eq!(datafile.get_integer_clamped(3, -10, -1), -1);
+    }
+
+    #[test]
+    fn test_datafile_enum() {
+        const VARIANTS: &[&str] = &["easy", "normal", "hard"];
+
+        let mut datafile = get_datafile();
+        datafile.set_string_list(&["hard", "HARD", "impossible"]);
+
+        assert_eq!(datafile.get_enum(0, VARIANTS, "normal"), "hard");
+        assert_eq!(datafile.get_enum(1, VARIANTS, "normal"), "normal");
+        assert_eq!(datafile.get_enum(2, VARIANTS, "normal"), "normal");
+        assert_eq!(datafile.get_enum(3, VARIANTS, "easy"), "easy");
+
+        datafile.set_case_insensitive(true);
+        assert_eq!(datafile.get_enum(1, VARIANTS, "normal"), "hard");
+
+        assert!(datafile.set_enum("Easy", 3, VARIANTS).is_ok());
+        assert_eq!(datafile.get_string(3), "easy");
+        assert_eq!(
+            datafile.set_enum("impossible", 0, VARIANTS),
+            Err(DatafileError::UnknownVariant {
+                value: "impossible".to_string(),
+                variants: vec!["easy".into(), "normal".into(), "hard".into()],
+            })
+        );
+        assert_eq!(datafile.get_string(0), "hard");
+    }
+
+    #[test]
+    fn test_datafile_long() {
+        let mut datafile = get_datafile();
+        datafile.set_long(i64::MAX - 1, 0);
+        datafile.set_unsigned_long(u64::MAX, 1);
+        datafile.set_value(i128::MIN, 2);
+
+        assert_eq!(datafile.get_string(0), "9223372036854775806");
+        assert_eq!(datafile.get_long(0), i64::MAX - 1);
+        assert_eq!(datafile.get_unsigned_long(1), u64::MAX);
+        assert_eq!(datafile.get_long(1), i64::MAX);
+        assert_eq!(datafile.get_value::<i128>(2), i128::MIN);
+        assert_eq!(datafile.get_unsigned_long(2), 0);
+    }
+
+    #[test]
+    fn test_datafile_bool() {
+        let mut datafile = get_datafile();
+        datafile.set_bool(true, 0);
+        datafile.set_bool(false, 1);
+        assert_eq!(datafile.get_string_list(), ["true", "false"]);
+        assert!(datafile.get_bool(0));
+        assert!(!datafile.get_bool(1));
+
+        let spellings = ["yes", "NO", "On", "off", "TRUE", "false", "1", "0"];
+        datafile.set_string_list(&spellings);
+
+        for index in 0..spellings.len() {
+            let expected = index % 2 == 0;
+            assert_eq!(datafile.get_bool(index), expected);
+            assert_eq!(datafile.get_bool_or(index, !expected), expected);
+        }
+
+        datafile.set_string_list(&["maybe"]);
+        assert!(!datafile.get_bool(0));
+        assert!(datafile.get_bool_or(0, true));
+        assert!(!datafile.get_bool_or(0, false));
+        assert!(datafile.get_bool_or(1, true));
+    }
+
+    #[test]
+    fn test_datafile_char() {
+        let mut datafile = get_datafile();
+        datafile.set_char('w', 0);
+        datafile.set_string("jump", 1);
+        datafile.set_string("", 2);
+
+        assert_eq!(datafile.get_char(0), 'w');
+        assert_eq!(datafile.get_char(1), 'j');
+        assert_eq!(datafile.get_char(2), '\0');
+        assert_eq!(datafile.get_char(3), '\0');
+    }
+
+    #[test]
+    fn test_datafile_owned_generic_values() {
+        fn read_first<T: crate::lexical::DeserializeOwned>(value: &str) -> T {
+            let mut datafile = get_datafile();
+            datafile.set_string(value, 0);
+            datafile.get_value(0)
+        }
+
+        assert_eq!(read_first::<i32>("42"), 42);
+        assert_eq!(read_first::<f32>("1.5"), 1.5);
+        assert_eq!(read_first::<String>("Javid"), "Javid");
     }
 }
@@ -0,0 +1,153 @@
+use crate::datafile::Datafile;
+
+/// A single difference between two datafiles, as returned by `Datafile::diff`. Paths use the
+/// same notation as `Datafile::get_path`, with siblings sharing a name told apart by their index,
+/// e.g. `enemy[1].name`. The node `diff` was called on has an empty path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A node only exists in the other datafile. Its children and values aren't listed
+    /// separately.
+    NodeAdded { path: String },
+    /// A node only exists in the original datafile. Its children and values aren't listed
+    /// separately.
+    NodeRemoved { path: String },
+    /// A value at the given index only exists in the other datafile.
+    ValueAdded {
+        path: String,
+        index: usize,
+        value: String,
+    },
+    /// A value at the given index only exists in the original datafile.
+    ValueRemoved {
+        path: String,
+        index: usize,
+        value: String,
+    },
+    /// A value at the given index differs between both datafiles.
+    ValueModified {
+        path: String,
+        index: usize,
+        old: String,
+        new: String,
+    },
+}
+
+/// Compares two nodes and everything below them, appending all differences to `changes`.
+pub(crate) fn diff_nodes(old: &Datafile, new: &Datafile, path: &str, changes: &mut Vec<Change>) {
+    diff_values(old, new, path, changes);
+
+    for (child_path, name, index, child) in old.indexed_children(path) {
+        match new.get_nth(name, index) {
+            Some(other) => diff_nodes(child, other, &child_path, changes),
+            None => changes.push(Change::NodeRemoved { path: child_path }),
+        }
+    }
+
+    for (child_path, name, index, _) in new.indexed_children(path) {
+        if old.get_nth(name, index).is_none() {
+            changes.push(Change::NodeAdded { path: child_path });
+        }
+    }
+}
+
+fn diff_values(old: &Datafile, new: &Datafile, path: &str, changes: &mut Vec<Change>) {
+    let count = old.contents.len().max(new.contents.len());
+
+    for index in 0..count {
+        let path = path.to_string();
+
+        match (old.contents.get(index), new.contents.get(index)) {
+            (Some(old), Some(new)) if old != new => changes.push(Change::ValueModified {
+                path,
+                index,
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            (Some(old), None) => changes.push(Change::ValueRemoved {
+                path,
+                index,
+                value: old.clone(),
+            }),
+            (None, Some(new)) => changes.push(Change::ValueAdded {
+                path,
+                index,
+                value: new.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let mut old = Datafile::new(None, None);
+        old.get("name").set_string("Javid", 0);
+        old.get("code").set_string_list(&["c++", "vhdl", "lua"]);
+        old.get("pc").get("ram").set_integer(32, 0);
+        old.get("removed").get("child").set_integer(1, 0);
+
+        let mut new = old.clone();
+        new.get("name").set_string("Jx9", 0);
+        new.get("code").set_string_list(&["c++", "vhdl"]);
+        new.get("pc").get("gpu").set_string("rtx", 0);
+        new.rename("removed", "renamed");
+
+        assert_eq!(
+            old.diff(&new),
+            [
+                Change::ValueModified {
+                    path: "name".to_string(),
+                    index: 0,
+                    old: "Javid".to_string(),
+                    new: "Jx9".to_string(),
+                },
+                Change::ValueRemoved {
+                    path: "code".to_string(),
+                    index: 2,
+                    value: "lua".to_string(),
+                },
+                Change::NodeAdded {
+                    path: "pc.gpu".to_string(),
+                },
+                Change::NodeRemoved {
+                    path: "removed".to_string(),
+                },
+                Change::NodeAdded {
+                    path: "renamed".to_string(),
+                },
+            ]
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_duplicate_siblings() {
+        let mut old = Datafile::new(None, None);
+        old.get_path_mut("enemy[0].name").set_string("orc", 0);
+        old.get_path_mut("enemy[1].name").set_string("goblin", 0);
+
+        let mut new = old.clone();
+        new.get_path_mut("enemy[1].name").set_string("troll", 0);
+        new.get_path_mut("enemy[2].name").set_string("dragon", 0);
+
+        assert_eq!(
+            old.diff(&new),
+            [
+                Change::ValueModified {
+                    path: "enemy[1].name".to_string(),
+                    index: 0,
+                    old: "goblin".to_string(),
+                    new: "troll".to_string(),
+                },
+                Change::NodeAdded {
+                    path: "enemy[2]".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error raised when a datafile is configured, built or processed in an invalid way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DatafileError {
+    /// The whitespace sequence used for indentation contains characters other than whitespace,
+    /// which would corrupt the written file.
+    InvalidWhitespaceSequence(String),
+    /// The quote character is whitespace or has a meaning of its own in the file format, e.g. the
+    /// list separator.
+    InvalidQuoteChar(char),
+    /// The key of the node at the given path can't be represented in the file format, see
+    /// `Datafile::is_valid_key`.
+    InvalidKey(String),
+    /// The value at the given index of the node at the given path contains quotation marks or line
+    /// breaks, which can't be represented in the file format.
+    InvalidValue { path: String, index: usize },
+    /// A value was about to be set that isn't one of the variants it's restricted to, see
+    /// `Datafile::set_enum`.
+    UnknownVariant {
+        value: String,
+        variants: Vec<String>,
+    },
+    /// The node at the given path has neither values nor children. It's written without a value,
+    /// which the reader skips.
+    EmptyNode(String),
+    /// A value was about to be set to an index beyond the maximum number of values of a node.
+    IndexOutOfRange {
+        index: usize,
+        max_value_count: usize,
+    },
+    /// The line with the given number of a datafile being read is malformed.
+    Syntax { line: usize, message: String },
+    /// Reading or writing a datafile failed for a reason other than its content, e.g. a missing
+    /// file, see the `From<std::io::Error>` implementation.
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+impl DatafileError {
+    /// Returns the error a failed read was caused by, if it was caused by a malformed datafile
+    /// rather than e.g. a missing file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::{Datafile, DatafileError, Reader};
+    /// let mut datafile = Datafile::new(None, None);
+    /// let mut reader = Reader::new(&mut datafile);
+    /// reader.options.strict_indentation = true;
+    ///
+    /// let error = reader.read_bytes(b"node\n{\nname = Javid\n}\n").unwrap_err();
+    /// let cause = DatafileError::from_io(&error).unwrap();
+    /// assert_eq!(cause.line_number(), Some(3));
+    /// ```
+    #[must_use]
+    pub fn from_io(error: &std::io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+
+    /// Returns the number of the line the error was found on, if it was found while reading.
+    #[must_use]
+    pub const fn line_number(&self) -> Option<usize> {
+        match self {
+            Self::Syntax { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the error is caused by the file or stream being read or written, e.g. a
+    /// missing file, rather than by the datafile itself.
+    #[must_use]
+    pub const fn is_io(&self) -> bool {
+        matches!(self, Self::Io { .. })
+    }
+
+    /// Checks whether the error is caused by a malformed datafile being read, e.g. a stray
+    /// closing brace.
+    #[must_use]
+    pub const fn is_structural(&self) -> bool {
+        matches!(self, Self::Syntax { .. })
+    }
+
+    /// Checks whether the error is caused by an invalid configuration of a datafile, e.g. a
+    /// reserved quote character.
+    #[must_use]
+    pub const fn is_configuration(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidWhitespaceSequence(_) | Self::InvalidQuoteChar(_)
+        )
+    }
+
+    /// Checks whether the error is caused by a node that can't be written and read back
+    /// unchanged, as reported by `Datafile::validate`.
+    #[must_use]
+    pub const fn is_unwritable(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidKey(_) | Self::InvalidValue { .. } | Self::EmptyNode(_)
+        )
+    }
+
+    /// Checks whether the error is caused by a value that was rejected while being set, e.g. one
+    /// beyond the maximum number of values.
+    #[must_use]
+    pub const fn is_rejected_value(&self) -> bool {
+        matches!(
+            self,
+            Self::IndexOutOfRange { .. } | Self::UnknownVariant { .. }
+        )
+    }
+}
+
+impl Display for DatafileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidWhitespaceSequence(sequence) => {
+                write!(
+                    f,
+                    "Whitespace sequence {sequence:?} contains non-whitespace characters"
+                )
+            }
+            Self::InvalidQuoteChar(quote_char) => {
+                write!(f, "Quote character {quote_char:?} is reserved")
+            }
+            Self::InvalidKey(path) => write!(f, "Node {path:?} has a key that can't be written"),
+            Self::InvalidValue { path, index } => {
+                write!(f, "Value {index} of node {path:?} can't be written")
+            }
+            Self::UnknownVariant { value, variants } => {
+                write!(f, "Value {value:?} is not one of {variants:?}")
+            }
+            Self::EmptyNode(path) => {
+                write!(f, "Node {path:?} has neither values nor children")
+            }
+            Self::IndexOutOfRange {
+                index,
+                max_value_count,
+            } => write!(
+                f,
+                "Index {index} exceeds the maximum number of {max_value_count} values"
+            ),
+            Self::Syntax { line, message } => write!(f, "Error reading line {line}: {message}"),
+            Self::Io { message, .. } => write!(f, "I/O error: {message}"),
+        }
+    }
+}
+
+impl Error for DatafileError {}
+
+/// Converts the error of a failed read or write, so callers can handle a single error type. An
+/// error caused by a malformed datafile is converted to its cause, see `DatafileError::from_io`,
+/// any other error to `DatafileError::Io`.
+impl From<std::io::Error> for DatafileError {
+    fn from(error: std::io::Error) -> Self {
+        Self::from_io(&error).cloned().unwrap_or_else(|| Self::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predicates() {
+        let errors = [
+            DatafileError::InvalidWhitespaceSequence("x".to_string()),
+            DatafileError::InvalidQuoteChar('='),
+            DatafileError::InvalidKey("a".to_string()),
+            DatafileError::InvalidValue {
+                path: "a".to_string(),
+                index: 0,
+            },
+            DatafileError::EmptyNode("a".to_string()),
+            DatafileError::IndexOutOfRange {
+                index: 4,
+                max_value_count: 4,
+            },
+            DatafileError::UnknownVariant {
+                value: "a".to_string(),
+                variants: vec![],
+            },
+            DatafileError::Syntax {
+                line: 3,
+                message: "closing brace without a node".to_string(),
+            },
+            DatafileError::Io {
+                kind: std::io::ErrorKind::NotFound,
+                message: "missing".to_string(),
+            },
+        ];
+
+        let kinds: Vec<_> = errors
+            .iter()
+            .map(|error| {
+                (
+                    error.is_configuration(),
+                    error.is_unwritable(),
+                    error.is_rejected_value(),
+                    error.is_structural(),
+                    error.is_io(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                (true, false, false, false, false),
+                (true, false, false, false, false),
+                (false, true, false, false, false),
+                (false, true, false, false, false),
+                (false, true, false, false, false),
+                (false, false, true, false, false),
+                (false, false, true, false, false),
+                (false, false, false, true, false),
+                (false, false, false, false, true),
+            ]
+        );
+
+        let line_numbers: Vec<_> = errors.iter().map(DatafileError::line_number).collect();
+        assert_eq!(
+            line_numbers,
+            [None, None, None, None, None, None, None, Some(3), None]
+        );
+    }
+
+    #[test]
+    fn test_from_io() {
+        let error = std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            DatafileError::Syntax {
+                line: 2,
+                message: "unexpected".to_string(),
+            },
+        );
+
+        assert_eq!(
+            DatafileError::from_io(&error).unwrap().line_number(),
+            Some(2)
+        );
+        assert_eq!(error.to_string(), "Error reading line 2: unexpected");
+
+        let cause = DatafileError::from(error);
+        assert!(cause.is_structural());
+        assert!(!cause.is_io());
+
+        let error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert_eq!(DatafileError::from_io(&error), None);
+
+        let cause = DatafileError::from(error);
+        assert!(cause.is_io());
+        assert_eq!(
+            cause,
+            DatafileError::Io {
+                kind: std::io::ErrorKind::NotFound,
+                message: "missing".to_string(),
+            }
+        );
+        assert_eq!(cause.to_string(), "I/O error: missing");
+    }
+}
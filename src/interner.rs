@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Hands out shared copies of strings, so repeated keys only have to be stored once.
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Returns the stored copy of the given string, storing it first if it wasn't seen before.
+    pub(crate) fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return Arc::clone(interned);
+        }
+
+        let interned: Arc<str> = Arc::from(string);
+        self.strings.insert(Arc::clone(&interned));
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_storage() {
+        let mut interner = Interner::default();
+        let first = interner.intern("x");
+        let second = interner.intern("x");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &interner.intern("y")));
+    }
+}
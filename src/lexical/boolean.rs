@@ -0,0 +1,62 @@
+use crate::lexical::{Deserialize, Serialize};
+
+impl Serialize for bool {
+    fn serialize(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Deserialize<'_> for bool {
+    fn deserialize(data: &str) -> Self {
+        parse_bool(data).unwrap_or(false)
+    }
+}
+
+/// Parses the spellings of a boolean a non-programmer might use, ignoring case and surrounding
+/// whitespace. Returns `None` if the data isn't any of them.
+pub(crate) fn parse_bool(data: &str) -> Option<bool> {
+    const TRUE: [&str; 4] = ["true", "yes", "on", "1"];
+    const FALSE: [&str; 4] = ["false", "no", "off", "0"];
+
+    let data = data.trim();
+
+    if TRUE
+        .iter()
+        .any(|spelling| spelling.eq_ignore_ascii_case(data))
+    {
+        Some(true)
+    } else if FALSE
+        .iter()
+        .any(|spelling| spelling.eq_ignore_ascii_case(data))
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(true.serialize(), "true");
+        assert_eq!(false.serialize(), "false");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        for spelling in ["true", "TRUE", "Yes", "on", "ON", "1"] {
+            assert!(bool::deserialize(spelling), "{spelling}");
+        }
+
+        for spelling in ["false", "False", "no", "NO", "off", "Off", "0"] {
+            assert!(!bool::deserialize(spelling), "{spelling}");
+        }
+
+        assert!(!bool::deserialize("maybe"));
+        assert_eq!(parse_bool("maybe"), None);
+        assert_eq!(parse_bool(""), None);
+    }
+}
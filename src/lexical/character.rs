@@ -0,0 +1,33 @@
+use crate::lexical::{Deserialize, Serialize};
+
+impl Serialize for char {
+    fn serialize(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Deserialize<'_> for char {
+    fn deserialize(data: &str) -> Self {
+        // Only the first character is kept, an empty value becomes '\0'.
+        data.chars().next().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!('a'.serialize(), "a");
+        assert_eq!('ä'.serialize(), "ä");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(char::deserialize("a"), 'a');
+        assert_eq!(char::deserialize("abc"), 'a');
+        assert_eq!(char::deserialize("ä"), 'ä');
+        assert_eq!(char::deserialize(""), '\0');
+    }
+}
@@ -1,16 +1,135 @@
-use crate::lexical::Serializable;
+use crate::lexical::{Deserialize, Serialize};
 
-impl Serializable<'_> for i32 {
+impl Serialize for i32 {
     fn serialize(&self) -> String {
         self.to_string()
     }
+}
 
+impl Deserialize<'_> for i32 {
     #[allow(clippy::cast_possible_truncation)]
     fn deserialize(data: &str) -> Self {
         data.parse::<Self>()
+            .ok()
+            .or_else(|| {
+                // Literals that don't fit are clamped, just like floats are.
+                let value = parse_prefixed(data)
+                    .or_else(|| parse_grouped(data))
+                    .or_else(|| parse_grouped_real(data))?;
+                Some(Self::try_from(value).unwrap_or(if value < 0 { Self::MIN } else { Self::MAX }))
+            })
             // If a float is passed, we just truncate it.
-            .unwrap_or_else(|_| data.replace(',', ".").parse::<f32>().unwrap_or(0.0) as Self)
+            .unwrap_or_else(|| data.replace(',', ".").parse::<f32>().unwrap_or(0.0) as Self)
+    }
+}
+
+macro_rules! impl_wide_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize(&self) -> String {
+                    self.to_string()
+                }
+            }
+
+            impl Deserialize<'_> for $ty {
+                fn deserialize(data: &str) -> Self {
+                    // Unlike for `i32`, reals aren't accepted, as parsing through a float would
+                    // lose the precision of large integers. Values that don't fit are clamped.
+                    data.parse::<Self>()
+                        .ok()
+                        .or_else(|| {
+                            let value = data
+                                .parse::<i128>()
+                                .ok()
+                                .or_else(|| parse_prefixed(data))
+                                .or_else(|| parse_grouped(data))?;
+
+                            Some(Self::try_from(value).unwrap_or(if value < 0 {
+                                Self::MIN
+                            } else {
+                                Self::MAX
+                            }))
+                        })
+                        .unwrap_or(0)
+                }
+            }
+        )*
+    };
+}
+
+impl_wide_integer!(i64, u64, i128);
+
+/// Parses an integer literal with a `0x`, `0b` or `0o` prefix for hexadecimal, binary or octal
+/// notation, optionally preceded by a sign, e.g. `0xFF` or `-0b1010`.
+pub(crate) fn parse_prefixed(data: &str) -> Option<i128> {
+    let (negative, digits) = match data.as_bytes().first()? {
+        b'-' => (true, &data[1..]),
+        b'+' => (false, &data[1..]),
+        _ => (false, data),
+    };
+
+    let radix = match digits.get(..2)? {
+        "0x" | "0X" => 16,
+        "0b" | "0B" => 2,
+        "0o" | "0O" => 8,
+        _ => return None,
+    };
+
+    // The digits must not carry a sign of their own.
+    let digits = &digits[2..];
+    if digits.starts_with(['+', '-']) {
+        return None;
     }
+
+    let value = i128::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Parses an integer whose digits are grouped in threes by `,` or `_`, e.g. `1,500`,
+/// `1,000,000` or `-1_000`, using the same separator throughout. A `,` that doesn't separate
+/// groups of three, like in `1,5`, is left to the parsing of reals as a decimal comma.
+pub(crate) fn parse_grouped(data: &str) -> Option<i128> {
+    let digits = data.strip_prefix(['-', '+']).unwrap_or(data);
+
+    let separator = digits.chars().find(|char| !char.is_ascii_digit())?;
+    if separator != ',' && separator != '_' {
+        return None;
+    }
+
+    let mut groups = digits.split(separator);
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 {
+        return None;
+    }
+
+    let mut ungrouped = format!("{}{first}", &data[..data.len() - digits.len()]);
+    for group in groups {
+        if group.len() != 3 || !group.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+
+        ungrouped.push_str(group);
+    }
+
+    ungrouped.parse().ok()
+}
+
+/// Parses a real whose integer part is grouped by `,` or `_`, e.g. `1,234.5`, returning the
+/// truncated integer part. The `.` makes clear the `,` isn't a decimal comma.
+pub(crate) fn parse_grouped_real(data: &str) -> Option<i128> {
+    let (integer, fraction) = data.split_once('.')?;
+    if fraction.is_empty() || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    parse_grouped(integer)
+}
+
+/// Formats an integer in hexadecimal notation, e.g. `0xFF` or `-0x10`.
+pub(crate) fn to_hex(value: i32) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    format!("{sign}0x{:X}", value.unsigned_abs())
 }
 
 #[cfg(test)]
@@ -30,4 +149,67 @@ mod tests {
         assert_eq!(i32::deserialize("1.5"), 1);
         assert_eq!(i32::deserialize("1,5"), 1);
     }
+
+    #[test]
+    fn test_deserialize_prefixed() {
+        assert_eq!(i32::deserialize("0xFF"), 255);
+        assert_eq!(i32::deserialize("0b1010"), 10);
+        assert_eq!(i32::deserialize("-0x10"), -16);
+        assert_eq!(i32::deserialize("+0o17"), 15);
+        assert_eq!(i32::deserialize("0x1FFFFFFFF"), i32::MAX);
+        assert_eq!(i32::deserialize("0x-1"), 0);
+        assert_eq!(i32::deserialize("0xZZ"), 0);
+    }
+
+    #[test]
+    fn test_deserialize_grouped() {
+        assert_eq!(i32::deserialize("1,000,000"), 1_000_000);
+        assert_eq!(i32::deserialize("-12_345"), -12_345);
+        assert_eq!(i32::deserialize("1_500"), 1500);
+        assert_eq!(i32::deserialize("1,234.5"), 1234);
+        assert_eq!(i32::deserialize("-1,234,567.9"), -1_234_567);
+        assert_eq!(i32::deserialize("1,00,000"), 0);
+        assert_eq!(i32::deserialize("1,000_000"), 0);
+        assert_eq!(i32::deserialize("1.000.000"), 0);
+    }
+
+    #[test]
+    fn test_deserialize_grouped_like_reals() {
+        // Digits grouped in threes are always an integer, any other comma is a decimal comma.
+        assert_eq!(i32::deserialize("1,500"), 1500);
+        assert_eq!(i32::deserialize("12,345"), 12_345);
+        assert_eq!(i32::deserialize("-123,456"), -123_456);
+        assert_eq!(i32::deserialize("1,000,000"), 1_000_000);
+        assert_eq!(i64::deserialize("1,500"), 1500);
+        assert_eq!(i32::deserialize("1,5"), 1);
+        assert_eq!(i32::deserialize("12,34"), 12);
+        assert_eq!(i64::deserialize("1,5"), 0);
+
+        // Only `,` and `_` group digits.
+        assert_eq!(i32::deserialize("1x000"), 0);
+        assert_eq!(i32::deserialize("1 000"), 0);
+        assert_eq!(i32::deserialize("1'000'000"), 0);
+        assert_eq!(i64::deserialize("1x000"), 0);
+    }
+
+    #[test]
+    fn test_deserialize_wide() {
+        assert_eq!(i64::deserialize("9223372036854775807"), i64::MAX);
+        assert_eq!(i64::deserialize("9223372036854775806"), i64::MAX - 1);
+        assert_eq!(i64::deserialize("9223372036854775808"), i64::MAX);
+        assert_eq!(i64::deserialize("-9223372036854775809"), i64::MIN);
+        assert_eq!(u64::deserialize("18446744073709551615"), u64::MAX);
+        assert_eq!(u64::deserialize("-1"), 0);
+        assert_eq!(i128::deserialize(&i128::MAX.serialize()), i128::MAX);
+        assert_eq!(i64::deserialize("0x7FFFFFFFFFFFFFFF"), i64::MAX);
+        assert_eq!(i64::deserialize("1,000,000,000,000"), 1_000_000_000_000);
+        assert_eq!(i64::deserialize("1.5"), 0);
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(255), "0xFF");
+        assert_eq!(to_hex(-16), "-0x10");
+        assert_eq!(to_hex(i32::MIN), "-0x80000000");
+    }
 }
@@ -1,11 +1,52 @@
+mod boolean;
+mod character;
 mod integer;
 mod real;
 mod string;
+mod unsigned;
 
-pub trait Serializable<'a> {
+pub(crate) use boolean::parse_bool;
+pub(crate) use integer::to_hex;
+pub(crate) use real::parse_real;
+
+/// A type that can be stored as a value of a datafile.
+pub trait Serialize {
+    /// Converts the value into the string it's stored as.
     fn serialize(&self) -> String;
+}
+
+/// A type that can be read back from a value of a datafile. Deserializing never fails, malformed
+/// data is coerced into a sensible value instead, e.g. `0` for numbers that can't be parsed.
+///
+/// The lifetime lets borrowed types like `&str` point into the datafile. Owned types implement
+/// this for every lifetime, see [`DeserializeOwned`].
+pub trait Deserialize<'a>: Sized {
+    /// Converts a stored string back into a value.
+    fn deserialize(data: &'a str) -> Self;
+}
+
+/// A type that can be deserialized without borrowing from the input, e.g. `i32` or `String`.
+///
+/// This is implemented automatically and is the bound to use when the result has to outlive the
+/// datafile it was read from.
+pub trait DeserializeOwned: for<'a> Deserialize<'a> {}
+
+impl<T> DeserializeOwned for T where T: for<'a> Deserialize<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deserialize_owned<T: DeserializeOwned>(data: &str) -> T {
+        // The input is dropped before the result is returned.
+        let owned = data.to_owned();
+        T::deserialize(&owned)
+    }
 
-    fn deserialize(data: &'a str) -> Self
-    where
-        Self: Sized;
+    #[test]
+    fn test_deserialize_owned() {
+        assert_eq!(deserialize_owned::<i32>("42"), 42);
+        assert_eq!(deserialize_owned::<f32>("1.5"), 1.5);
+        assert_eq!(deserialize_owned::<String>("Javid"), "Javid");
+    }
 }
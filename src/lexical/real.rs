@@ -1,16 +1,24 @@
-use crate::lexical::Serializable;
+use crate::lexical::{Deserialize, Serialize};
 
-impl Serializable<'_> for f32 {
+impl Serialize for f32 {
     fn serialize(&self) -> String {
         self.to_string()
     }
+}
 
+impl Deserialize<'_> for f32 {
     fn deserialize(data: &str) -> Self {
-        data.parse::<Self>()
-            .unwrap_or_else(|_| data.replace(',', ".").parse::<Self>().unwrap_or_default())
+        parse_real(data).unwrap_or_default()
     }
 }
 
+/// Parses a real, accepting a decimal comma as well as a decimal point.
+pub(crate) fn parse_real(data: &str) -> Option<f32> {
+    data.parse()
+        .ok()
+        .or_else(|| data.replace(',', ".").parse().ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
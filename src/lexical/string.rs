@@ -1,20 +1,24 @@
-use crate::lexical::Serializable;
+use crate::lexical::{Deserialize, Serialize};
 
-impl Serializable<'_> for String {
+impl Serialize for String {
     fn serialize(&self) -> String {
         self.clone()
     }
+}
 
+impl Deserialize<'_> for String {
     fn deserialize(data: &str) -> Self {
         data.to_owned()
     }
 }
 
-impl<'a> Serializable<'a> for &'a str {
+impl Serialize for &str {
     fn serialize(&self) -> String {
         (*self).to_string()
     }
+}
 
+impl<'a> Deserialize<'a> for &'a str {
     fn deserialize(data: &'a str) -> Self {
         data
     }
@@ -0,0 +1,65 @@
+use crate::lexical::integer::{parse_grouped, parse_grouped_real, parse_prefixed};
+use crate::lexical::{Deserialize, Serialize};
+
+macro_rules! impl_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize(&self) -> String {
+                    self.to_string()
+                }
+            }
+
+            impl Deserialize<'_> for $ty {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                fn deserialize(data: &str) -> Self {
+                    // Float to integer casts saturate, so negative values clamp to 0 and floats
+                    // are truncated. Prefixed literals are clamped the same way.
+                    data.parse::<Self>()
+                        .ok()
+                        .or_else(|| {
+                            let value = parse_prefixed(data)
+                                .or_else(|| parse_grouped(data))
+                                .or_else(|| parse_grouped_real(data))?
+                                .max(0);
+                            Some(Self::try_from(value).unwrap_or(Self::MAX))
+                        })
+                        .unwrap_or_else(|| data.replace(',', ".").parse::<f64>().unwrap_or(0.0) as Self)
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u32, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(0_u32.serialize(), "0");
+        assert_eq!(42_usize.serialize(), "42");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(u32::deserialize("42"), 42);
+        assert_eq!(u32::deserialize("-3"), 0);
+        assert_eq!(u32::deserialize("1.9"), 1);
+        assert_eq!(u32::deserialize("1,9"), 1);
+        assert_eq!(u32::deserialize("abc"), 0);
+        assert_eq!(usize::deserialize("42"), 42);
+        assert_eq!(usize::deserialize("-3"), 0);
+        assert_eq!(u32::deserialize("0xFF"), 255);
+        assert_eq!(u32::deserialize("0b1010"), 10);
+        assert_eq!(u32::deserialize("-0x10"), 0);
+        assert_eq!(u32::deserialize("0x1FFFFFFFF"), u32::MAX);
+        assert_eq!(u32::deserialize("1,000,000"), 1_000_000);
+        assert_eq!(u32::deserialize("1,234.5"), 1234);
+        assert_eq!(u32::deserialize("1,500"), 1500);
+        assert_eq!(u32::deserialize("12,345"), 12_345);
+        assert_eq!(u32::deserialize("1x000"), 0);
+    }
+}
@@ -20,6 +20,7 @@
 //! ```no_run
 //! use olc_datafile_rust::Datafile;
 //!
+//! let path = std::env::temp_dir().join("datafile.txt");
 //! let mut datafile = Datafile::new(Some(','), Some(" "));
 //!
 //! let some_node = datafile.get("some_node");
@@ -37,13 +38,13 @@
 //! pc.get("ram").set_integer(32, 0);
 //!
 //! datafile
-//!     .write("./datafile.txt")
+//!     .write(&path)
 //!     .expect("Failed to write datafile");
 //!
 //! let mut datafile = Datafile::new(Some(','), Some(" "));
 //!
 //! datafile
-//!     .read("./datafile.txt")
+//!     .read(&path)
 //!     .expect("Failed to read datafile");
 //!
 //! println!("{:?}", datafile.get("some_node"));
@@ -62,9 +63,11 @@
 //!       must be created.
 //!
 //! - Implementation differences:
-//!     * The original parser was implemented using a stack based approach. Whilst it's a good solution, this
-//!       implementation uses a recursion based approach. Not only was it easier to implement, but it also eliminated the
-//!       need to keep track of the references as it was done in the original implementation.
+//!     * The original parser was implemented using a stack based approach, keeping track of references to
+//!       the nodes being parsed. This implementation splits the parser into a scanner emitting events for every node,
+//!       value and comment, and a builder turning those events into a tree. Open nodes are kept on a stack of owned
+//!       datafiles and attached to their parent once they end, so no references into the tree have to be tracked. The
+//!       events are also available on their own through `Reader::parse_events`.
 //!     * Some internal methods were added to make the code a bit more readable. These methods are not part of the public
 //!       API, and comparing both codebases should still be trivial.
 //!     * The original implementation was done in a single header file. I opted for a multi-file approach.
@@ -106,18 +109,34 @@
 
 #[rustfmt::skip]
 pub use {
-    datafile::Datafile,
-    processor::reader::Reader,
-    processor::writer::Writer,
+    builder::DatafileBuilder,
+    datafile::{Datafile, DatafilePath, Entry},
+    diff::Change,
+    error::DatafileError,
+    lexical::{Deserialize, DeserializeOwned, Serialize},
+    processor::reader::{DuplicateKeys, KeyTransform, ParseEvent, ReadOptions, Reader},
+    processor::writer::{WriteOptions, Writer},
 };
 
+/// The `builder` module contains the `DatafileBuilder` struct, used to configure a `Datafile`.
+pub mod builder;
+
 /// The `datafile` module contains the `Datafile` struct and its methods.
 pub mod datafile;
 
+/// The `diff` module contains the `Change` enum, describing differences between datafiles.
+pub mod diff;
+
+/// The `error` module contains the `DatafileError` enum.
+pub mod error;
+
 /// The `processor` module contains the `Reader` and `Writer` structs and their methods.
 /// These structs are used to read and write datafiles, respectively. In theory, accessing
 /// these structs directly is not necessary, as the `Datafile` struct provides a more
 /// convenient interface.
 pub mod processor;
 
+mod interner;
 mod lexical;
+#[cfg(test)]
+mod test_utils;
@@ -1,9 +1,13 @@
-use std::borrow::BorrowMut;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+use std::path::Path;
+use std::sync::Arc;
 
 use crate::datafile::Datafile;
+use crate::error::DatafileError;
+use crate::interner::Interner;
 
 /// A reader for a datafile. This is used to parse a file from disk into a datafile. This is
 /// not intended to be used directly, but rather through the `Datafile::read` method. Though
@@ -22,99 +26,917 @@ use crate::datafile::Datafile;
 /// This function will return an error if the file cannot be read from.
 #[derive(Debug)]
 pub struct Reader<'a> {
+    /// The options controlling how the datafile is parsed.
+    pub options: ReadOptions,
+
     top_node: RefCell<&'a mut Datafile>,
 }
 
+/// An event emitted by `Reader::parse_events` while scanning a datafile. The borrowed strings
+/// point into the line that is currently being parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseEvent<'a> {
+    /// A new node with the given name starts. Everything up to the matching `NodeEnd` belongs to
+    /// it. If this directly follows a `Value` with the same key, e.g. `name = Javid` followed by
+    /// `{`, the node holds both those values and children.
+    NodeStart(&'a str),
+    /// The most recently started node ends.
+    NodeEnd,
+    /// A key with its list of values, e.g. `name = Javid`.
+    Value { key: &'a str, values: Vec<String> },
+    /// A comment line, including the leading `#`.
+    Comment(&'a str),
+}
+
+/// Options controlling how a datafile is parsed. The defaults reproduce the behavior of the
+/// original implementation, apart from the size limits.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::{Datafile, ReadOptions, Reader};
+/// let options = ReadOptions {
+///     inline_comments: true,
+///     ..ReadOptions::default()
+/// };
+///
+/// let mut datafile = Datafile::new(None, None);
+/// Reader::with_options(&mut datafile, options)
+///     .read_bytes(b"volume = 5 # loudness\n")
+///     .unwrap();
+///
+/// assert_eq!(datafile.get("volume").get_integer(0), 5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Whether to check that every line is indented according to its depth in the tree, using
+    /// the whitespace sequence of the datafile. Reading fails with the number of the first line
+    /// that isn't, which helps to find missing or misplaced braces in hand-edited files.
+    /// Defaults to `false`.
+    pub strict_indentation: bool,
+    /// Whether to replace the whitespace sequence of the datafile with the indentation the file
+    /// uses, taken from its first indented line. Writing the datafile back then keeps the
+    /// original indentation, e.g. tabs, regardless of the sequence it was configured with. Files
+    /// without indented lines leave the sequence unchanged. Defaults to `false`.
+    pub detect_indentation: bool,
+    /// Whether to record the line every node was parsed from, see `Datafile::source_line`.
+    /// Defaults to `false`.
+    pub record_source_lines: bool,
+    /// The maximum length of a single line in bytes. Reading fails once a line exceeds it, before
+    /// the rest of the line is loaded into memory. Defaults to 1 MiB.
+    pub max_line_length: usize,
+    /// The maximum size of the whole input in bytes. Reading fails once more than this has been
+    /// read. Defaults to 1 GiB.
+    pub max_total_bytes: usize,
+    /// Whether nodes sharing a name, like the `x` and `y` of thousands of entities, should share
+    /// the storage of that name instead of each holding a copy of it. This reduces memory usage
+    /// and allocations for large files with many repeated keys. Defaults to `false`.
+    pub intern_keys: bool,
+    /// Whether everything following an unquoted `#` on a line should be ignored, e.g. the comment
+    /// in `volume = 5 # loudness`. A `#` within quotation marks is kept. This is disabled by
+    /// default, as values may contain `#` without being quoted. Defaults to `false`.
+    pub inline_comments: bool,
+    /// Whether to replace invalid UTF-8 with `U+FFFD REPLACEMENT CHARACTER` instead of failing,
+    /// like `Reader::read_lossy` does. Defaults to `false`.
+    pub lossy: bool,
+    /// Whether a quoted value may span multiple lines, e.g. an embedded text block. A line with an
+    /// unterminated quotation mark is continued on the following lines until the closing one,
+    /// with the line breaks kept in the value. Defaults to `false`.
+    pub multiline_values: bool,
+    /// Whether the whitespace within quotation marks is removed as well, e.g. `"  x  "` is read
+    /// as `x`, like in the original implementation. Without it, such a value is read as `  x  `,
+    /// so values the writer delimits because of their padding are read back unchanged. The
+    /// whitespace surrounding unquoted values and quotation marks is always removed. Defaults to
+    /// `true`.
+    pub trim_values: bool,
+    /// The unquoted value that marks a value as null instead of empty, e.g. `~`, see
+    /// `Datafile::is_null`. The same value delimited by quotation marks is read as is. Defaults
+    /// to `None`, so no value is null.
+    pub null_token: Option<String>,
+    /// Whether a key on a line of its own, e.g. `fullscreen`, is a flag instead of the name of a
+    /// node, if the next line that isn't empty doesn't open a block. Flags are read as a value of
+    /// `true`, so they're written back as `fullscreen = true`. Defaults to `false`, so such a
+    /// key always starts a node, like in the original implementation.
+    pub bare_flags: bool,
+    /// The token separating keys from values, e.g. `:` for dialects using `name : Javid`. Only
+    /// the first token outside of quotation marks on a line separates them. Defaults to `=`.
+    pub assignment_token: Cow<'static, str>,
+    /// A function every key is passed through while reading, e.g. to strip a `@` marker some
+    /// exporters prefix keys with. It applies to the names of nodes and to the keys of values,
+    /// after quotation marks were removed. Defaults to `None`, so keys are read as is.
+    pub key_transform: Option<KeyTransform>,
+    /// Whether consecutive comment lines that aren't attached to a node as its doc comment, e.g.
+    /// a paragraph followed by an empty line, are kept as a single comment node. Its name holds
+    /// all lines, including their `#`, joined by line breaks, and the writer writes them back
+    /// line by line. Defaults to `false`, so every line is a comment node of its own.
+    pub group_comments: bool,
+    /// Whether comments are kept, either as comment nodes or as the doc comments of the nodes
+    /// following them. Without them, comment lines are skipped entirely, which speeds up parsing
+    /// heavily commented files. Defaults to `true`.
+    pub preserve_comments: bool,
+    /// What happens to the values of a key that appears more than once in the same node. Defaults
+    /// to `DuplicateKeys::Overwrite`, like in the original implementation.
+    pub duplicate_keys: DuplicateKeys,
+}
+
+/// How the reader handles a key that appears on more than one line of the same node, e.g. two
+/// `name = ...` lines, see `ReadOptions::duplicate_keys`.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::{Datafile, DuplicateKeys, ReadOptions, Reader};
+/// let options = ReadOptions {
+///     duplicate_keys: DuplicateKeys::Append,
+///     ..ReadOptions::default()
+/// };
+///
+/// let mut datafile = Datafile::new(None, None);
+/// Reader::with_options(&mut datafile, options)
+///     .read_bytes(b"tag = a\ntag = b, c\n")
+///     .unwrap();
+///
+/// assert_eq!(datafile.get("tag").get_string_list(), ["a", "b", "c"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// The values of a later line replace those of an earlier one index by index, so the first
+    /// values are lost. Values beyond the ones of the later line are kept.
+    Overwrite,
+    /// The values of a later line are appended to those of an earlier one, so all of them are
+    /// kept.
+    Append,
+}
+
+/// A function transforming the keys of a datafile while reading it, see
+/// `ReadOptions::key_transform`. Clones share the same function, and two transforms are only
+/// equal if they do.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::{Datafile, KeyTransform, ReadOptions, Reader};
+/// let options = ReadOptions {
+///     key_transform: Some(KeyTransform::new(|key| key.trim_start_matches('@').to_string())),
+///     ..ReadOptions::default()
+/// };
+///
+/// let mut datafile = Datafile::new(None, None);
+/// Reader::with_options(&mut datafile, options)
+///     .read_bytes(b"@name = Javid\n")
+///     .unwrap();
+///
+/// assert_eq!(datafile.get("name").get_string(0), "Javid");
+/// ```
+#[derive(Clone)]
+pub struct KeyTransform(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl KeyTransform {
+    /// Creates a transform from a function receiving a key and returning the key to use instead.
+    pub fn new<F: Fn(&str) -> String + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Transforms a single key.
+    #[must_use]
+    pub fn apply(&self, key: &str) -> String {
+        (self.0)(key)
+    }
+}
+
+impl std::fmt::Debug for KeyTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("KeyTransform(..)")
+    }
+}
+
+impl PartialEq for KeyTransform {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for KeyTransform {}
+
+impl ReadOptions {
+    /// Creates the default options, see the documentation of the individual fields.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strict_indentation: false,
+            detect_indentation: false,
+            record_source_lines: false,
+            max_line_length: 1 << 20,
+            max_total_bytes: 1 << 30,
+            intern_keys: false,
+            inline_comments: false,
+            lossy: false,
+            multiline_values: false,
+            trim_values: true,
+            null_token: None,
+            bare_flags: false,
+            assignment_token: Cow::Borrowed("="),
+            key_transform: None,
+            group_comments: false,
+            preserve_comments: true,
+            duplicate_keys: DuplicateKeys::Overwrite,
+        }
+    }
+}
+
+impl Default for ReadOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Reader<'a> {
     /// Creates a new reader for a datafile. Takes a mutable reference to a datafile as an
     /// argument and populates it with the contents of the file.
     pub fn new(datafile: &'a mut Datafile) -> Self {
+        Self::with_options(datafile, ReadOptions::new())
+    }
+
+    /// Creates a new reader for a datafile, parsing with the given options.
+    pub fn with_options(datafile: &'a mut Datafile, options: ReadOptions) -> Self {
         Self {
+            options,
             top_node: RefCell::new(datafile),
         }
     }
 
     /// Reads a datafile from disk. The top-level datafile should be specified in the structs
-    /// constructor. This will overwrite any data that is currently in the datafile.
+    /// constructor. This will overwrite any data that is currently in the datafile, while keeping
+    /// its configuration.
     ///
     /// # Errors
     ///
     /// This function will return an error if the file cannot be opened, or if the file cannot be
     /// read from.
-    pub fn read(&self, path: &str) -> std::io::Result<()> {
-        let reader = BufReader::new(File::open(path)?);
-        let lines = reader.lines().collect();
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.read_from(BufReader::new(File::open(path)?))
+    }
 
-        Self::read_inner(&mut self.top_node.borrow_mut(), &lines, 0)
+    /// Does the same as `read`, but doesn't fail on invalid UTF-8. Invalid byte sequences are
+    /// replaced with `U+FFFD REPLACEMENT CHARACTER` instead, so the rest of the file can still be
+    /// parsed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened, or if the file cannot be
+    /// read from.
+    pub fn read_lossy<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.read_lines(self.lines(BufReader::new(File::open(path)?), true))
+    }
+
+    /// Parses a datafile from a byte slice, e.g. one embedded with `include_bytes!`, the same way
+    /// `read` parses the contents of a file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data isn't valid UTF-8.
+    #[inline]
+    pub fn read_bytes(&self, data: &[u8]) -> std::io::Result<()> {
+        self.read_from(data)
+    }
+
+    /// Does the same as `read_bytes`, but replaces invalid UTF-8 with
+    /// `U+FFFD REPLACEMENT CHARACTER` instead of failing.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is malformed, e.g. with strict indentation.
+    #[inline]
+    pub fn read_bytes_lossy(&self, data: &[u8]) -> std::io::Result<()> {
+        self.read_lines(self.lines(data, true))
+    }
+
+    /// Parses a string into a new datafile with the default configuration and returns it, the
+    /// same way `read` parses the contents of a file. A shortcut for one-off parsing, which
+    /// doesn't require a datafile to be created and borrowed up front.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::Reader;
+    /// let mut datafile = Reader::parse("pc\n{\n\tram = 32\n}\n").unwrap();
+    /// assert_eq!(datafile.get("pc").get("ram").get_integer(0), 32);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the contents are malformed, e.g. exceed the default
+    /// size limits.
+    pub fn parse(contents: &str) -> std::io::Result<Datafile> {
+        let mut datafile = Datafile::default();
+        Reader::new(&mut datafile).read_from(contents.as_bytes())?;
+        Ok(datafile)
+    }
+
+    /// Parses a datafile from a string, the same way `read` parses the contents of a file.
+    #[cfg(test)]
+    pub(crate) fn read_str(&self, text: &str) -> std::io::Result<()> {
+        self.read_from(text.as_bytes())
+    }
+
+    /// Scans a datafile line by line and invokes the callback for every node, value and comment
+    /// found, without building a tree. This keeps memory usage bounded for very large files. The
+    /// list separator of the top-level datafile is used to split values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::{Datafile, ParseEvent, Reader};
+    /// let mut datafile = Datafile::new(None, None);
+    /// let reader = Reader::new(&mut datafile);
+    ///
+    /// let mut keys = vec![];
+    /// reader
+    ///     .parse_events("pc\n{\n\tram = 32\n}\n".as_bytes(), |event| {
+    ///         if let ParseEvent::Value { key, .. } = event {
+    ///             keys.push(key.to_string());
+    ///         }
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(keys, ["ram"]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the source cannot be read from.
+    pub fn parse_events<R: BufRead, F: FnMut(ParseEvent<'_>)>(
+        &self,
+        reader: R,
+        mut f: F,
+    ) -> std::io::Result<()> {
+        self.scanner()
+            .scan(self.lines(reader, false), None, &mut |_, event, _| f(event))
     }
 
-    /// Recursively parses a datafile node and it's children.
+    /// Parses a datafile like `read_bytes` does, but doesn't stop at the first syntax error. All
+    /// of them are collected and returned instead, along with the best-effort tree in the
+    /// top-level datafile. This includes problems `read` tolerates silently, like unterminated
+    /// quotation marks, stray closing braces or unclosed nodes. Useful for editors and for
+    /// validating user-submitted files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olc_datafile_rust::{Datafile, Reader};
+    /// let mut datafile = Datafile::new(None, None);
+    /// let reader = Reader::new(&mut datafile);
+    ///
+    /// let errors = reader.read_recovering("}\nname = \"Javid\n".as_bytes()).unwrap();
+    /// assert_eq!(errors.len(), 2);
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return an error if the file cannot be read from.
+    /// This function will return an error if the source cannot be read from, or if it exceeds the
+    /// size limits of the reader.
+    pub fn read_recovering<R: BufRead>(&self, reader: R) -> std::io::Result<Vec<DatafileError>> {
+        let mut errors = vec![];
+        self.build_tree(self.lines(reader, false), Some(&mut errors))?;
+        Ok(errors)
+    }
+
+    /// Reads only the node at the given path from a file, using the same notation as
+    /// `Datafile::get_property`. Branches that aren't part of the path are skipped while the file
+    /// is streamed, so no tree is built for them. The top-level datafile is left untouched, the
+    /// node is returned instead, configured like the top-level datafile.
+    ///
+    /// # Examples
     ///
-    /// # Note
+    /// ```no_run
+    /// # use olc_datafile_rust::{Datafile, Reader};
+    /// let mut datafile = Datafile::new(None, None);
+    /// let reader = Reader::new(&mut datafile);
     ///
-    /// No max depth is specified, so this function will continue to parse until it reaches the
-    /// end of the file. This also means that  this function may overflow the stack if the file
-    /// is too large. This is not a concern for the intended use of this library,
-    /// but it is something to be aware of.
-    fn read_inner(
-        parent_node: &mut Datafile,
-        lines: &Vec<Result<String, Error>>,
-        skip: usize,
+    /// let mut inventory = reader.read_subtree("save.txt", "world.player.inventory").unwrap();
+    /// println!("{}", inventory.get("gold").get_integer(0));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened or read from, or if it
+    /// contains no node at the given path.
+    pub fn read_subtree<P: AsRef<Path>>(&self, path: P, dotted: &str) -> std::io::Result<Datafile> {
+        self.subtree_from(self.lines(BufReader::new(File::open(path)?), false), dotted)
+    }
+
+    /// Builds a tree of only the nodes along the given path, then takes the node at its end.
+    fn subtree_from<I: Iterator<Item = std::io::Result<String>>>(
+        &self,
+        lines: I,
+        dotted: &str,
+    ) -> std::io::Result<Datafile> {
+        let scanner = self.scanner();
+        let top_node = self.top_node.borrow();
+        let mut partial = top_node.new_child();
+
+        let mut filter = PathFilter {
+            names: dotted
+                .split('.')
+                .map(|segment| Datafile::parse_path_segment(segment).0)
+                .collect(),
+            depth: 0,
+            skip_until: None,
+        };
+
+        let mut builder = TreeBuilder::new(&mut partial, self.options.record_source_lines);
+        builder.interner = self.options.intern_keys.then(Interner::default);
+        builder.group_comments = self.options.group_comments;
+        builder.duplicate_keys = self.options.duplicate_keys;
+
+        scanner.scan(lines, None, &mut |line_number, event, nulls| {
+            if filter.accepts(&event, |a, b| top_node.names_match(a, b)) {
+                builder.handle(line_number, event, nulls);
+            } else {
+                // The skipped event was the last one, so no node may be reopened by the next.
+                builder.after_value = false;
+            }
+        })?;
+        builder.finish();
+
+        if partial.get_path(dotted).is_none() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No node found at path {dotted}"),
+            ));
+        }
+
+        Ok(std::mem::take(partial.get_path_mut(dotted)))
+    }
+
+    #[inline]
+    pub(crate) fn read_from<R: BufRead>(&self, reader: R) -> std::io::Result<()> {
+        self.read_lines(self.lines(reader, false))
+    }
+
+    #[inline]
+    fn read_lines<I: Iterator<Item = std::io::Result<String>>>(
+        &self,
+        lines: I,
     ) -> std::io::Result<()> {
-        for (i, line) in lines.iter().skip(skip).enumerate() {
-            let line_number = i + 1;
-            let line = Self::trim_line(line.as_ref(), line_number)?;
+        self.build_tree(lines, None)
+    }
 
-            // An empty line or opening brace holds no meaning for the parser. We can skip it.
-            if line.is_empty() || line.starts_with('{') {
+    /// Builds the tree of the top-level datafile from the events of the given lines. Syntax
+    /// errors are collected instead of failing, if a list for them is given.
+    fn build_tree<I: Iterator<Item = std::io::Result<String>>>(
+        &self,
+        lines: I,
+        errors: Option<&mut Vec<DatafileError>>,
+    ) -> std::io::Result<()> {
+        let scanner = self.scanner();
+        let mut top_node = self.top_node.borrow_mut();
+        top_node.clear();
+
+        let mut builder = TreeBuilder::new(&mut top_node, self.options.record_source_lines);
+        builder.interner = self.options.intern_keys.then(Interner::default);
+        builder.group_comments = self.options.group_comments;
+        builder.duplicate_keys = self.options.duplicate_keys;
+
+        // Lines can only be indented once a block was opened, so the first indented line is
+        // located at the first level.
+        let mut indentation = None;
+        let lines = lines.inspect(|line| match line {
+            Ok(line) if self.options.detect_indentation && indentation.is_none() => {
+                let trimmed = line.trim_start();
+                if !trimmed.is_empty() && trimmed.len() < line.len() {
+                    indentation = Some(line[..line.len() - trimmed.len()].to_string());
+                }
+            }
+            _ => {}
+        });
+
+        scanner.scan(lines, errors, &mut |line_number, event, nulls| {
+            builder.handle(line_number, event, nulls);
+        })?;
+        builder.finish();
+
+        if let Some(indentation) = indentation {
+            top_node.whitespace_sequence = indentation;
+        }
+
+        Ok(())
+    }
+
+    fn scanner(&self) -> Scanner {
+        Scanner::new(&self.top_node.borrow(), &self.options)
+    }
+
+    /// Splits a source into lines like `BufRead::lines`, enforcing the size limits of the reader.
+    /// If `lossy` or the option of the same name is set, invalid UTF-8 is replaced instead of
+    /// failing.
+    fn lines<R: BufRead>(&self, reader: R, lossy: bool) -> BoundedLines<R> {
+        BoundedLines {
+            reader,
+            lossy: lossy || self.options.lossy,
+            max_line_length: self.options.max_line_length,
+            max_total_bytes: self.options.max_total_bytes,
+            remaining_bytes: self.options.max_total_bytes,
+            at_start: true,
+            done: false,
+        }
+    }
+}
+
+/// An iterator over the lines of a source that fails as soon as a line or the whole input
+/// exceeds its limit, so oversized input is never fully loaded into memory. A UTF-8 byte order
+/// mark at the start of the input is skipped.
+struct BoundedLines<R> {
+    reader: R,
+    lossy: bool,
+    max_line_length: usize,
+    max_total_bytes: usize,
+    remaining_bytes: usize,
+    at_start: bool,
+    done: bool,
+}
+
+/// The UTF-8 encoding of `U+FEFF`, which some editors put at the start of a file.
+const BYTE_ORDER_MARK: &[u8] = b"\xEF\xBB\xBF";
+
+impl<R: BufRead> BoundedLines<R> {
+    fn read_line(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        let mut read_any = false;
+
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+
+            if available.is_empty() {
+                return Ok(read_any.then_some(line));
+            }
+
+            let newline = available.iter().position(|&byte| byte == b'\n');
+            let chunk = &available[..newline.unwrap_or(available.len())];
+            let used = chunk.len() + usize::from(newline.is_some());
+
+            if line.len() + chunk.len() > self.max_line_length {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "line exceeds the maximum length of {} bytes",
+                        self.max_line_length
+                    ),
+                ));
+            }
+
+            if used > self.remaining_bytes {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "input exceeds the maximum size of {} bytes",
+                        self.max_total_bytes
+                    ),
+                ));
+            }
+
+            line.extend_from_slice(chunk);
+            read_any = true;
+            self.remaining_bytes -= used;
+            self.reader.consume(used);
+
+            if newline.is_some() {
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+
+                return Ok(Some(line));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BoundedLines<R> {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut line = match self.read_line() {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(error) => {
+                // The rest of an oversized line would be read as a new line otherwise.
+                self.done = true;
+                return Some(Err(error));
+            }
+        };
+
+        if std::mem::take(&mut self.at_start) && line.starts_with(BYTE_ORDER_MARK) {
+            line.drain(..BYTE_ORDER_MARK.len());
+        }
+
+        if self.lossy {
+            Some(Ok(String::from_utf8_lossy(&line).into_owned()))
+        } else {
+            Some(String::from_utf8(line).map_err(|_| {
+                Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+            }))
+        }
+    }
+}
+
+/// Splits lines into parse events, using the settings of the reader and its top-level datafile.
+struct Scanner {
+    list_separator: char,
+    quote_char: char,
+    /// The sequence every level must be indented with, if strict indentation is enabled.
+    indentation: Option<String>,
+    /// Whether comments may follow the content of a line.
+    inline_comments: bool,
+    /// Whether quoted values may continue on the following lines.
+    multiline_values: bool,
+    /// Whether the whitespace within quotation marks is removed as well.
+    trim_values: bool,
+    /// The unquoted value that marks a value as null, if any.
+    null_token: Option<String>,
+    /// Whether keys on a line of their own are flags, unless they're followed by a block.
+    bare_flags: bool,
+    /// The token separating keys from values.
+    assignment_token: String,
+    /// The function every key is passed through, if any.
+    key_transform: Option<KeyTransform>,
+    /// Whether comment lines are emitted as events, instead of being skipped.
+    preserve_comments: bool,
+}
+
+/// Splits a list of values the way the reader does with the default options, using the list
+/// separator and quotation mark of the given datafile, but keeping quoted whitespace.
+pub(crate) fn parse_raw_values(datafile: &Datafile, raw_value: &str) -> Vec<String> {
+    let options = ReadOptions {
+        trim_values: false,
+        ..ReadOptions::new()
+    };
+
+    Scanner::new(datafile, &options).parse_values(raw_value).0
+}
+
+impl Scanner {
+    /// Creates a scanner for the configuration of the given datafile.
+    fn new(datafile: &Datafile, options: &ReadOptions) -> Self {
+        Self {
+            list_separator: datafile.list_separator,
+            quote_char: datafile.quote_char,
+            indentation: options
+                .strict_indentation
+                .then(|| datafile.whitespace_sequence.clone()),
+            inline_comments: options.inline_comments,
+            multiline_values: options.multiline_values,
+            trim_values: options.trim_values,
+            null_token: options.null_token.clone(),
+            bare_flags: options.bare_flags,
+            assignment_token: options.assignment_token.to_string(),
+            key_transform: options.key_transform.clone(),
+            preserve_comments: options.preserve_comments,
+        }
+    }
+
+    /// Scans the given lines and invokes the callback with every event and the number of the line
+    /// it was found on, along with the indices of the values that are null, if the event holds
+    /// values. If a list for errors is given, syntax errors are collected in it instead
+    /// of failing, along with the problems that are otherwise tolerated.
+    fn scan<I, F>(
+        &self,
+        lines: I,
+        mut errors: Option<&mut Vec<DatafileError>>,
+        f: &mut F,
+    ) -> std::io::Result<()>
+    where
+        I: Iterator<Item = std::io::Result<String>>,
+        F: FnMut(usize, ParseEvent<'_>, &[usize]),
+    {
+        let mut depth: usize = 0;
+        let mut last_line_number = 0;
+        // The key of the previous line, if it was a value. A block following it belongs to it.
+        let mut value_key: Option<String> = None;
+        // A key on a line of its own, if it's only known to start a node once a block follows.
+        let mut bare_key: Option<(usize, String)> = None;
+
+        let mut lines = lines.enumerate();
+
+        while let Some((index, raw_line)) = lines.next() {
+            let line_number = index + 1;
+            let mut raw_line = Cow::Borrowed(Self::read_line(raw_line.as_ref(), line_number)?);
+            last_line_number = line_number;
+
+            // A quoted value that isn't closed on its line continues on the following ones.
+            while self.multiline_values && self.is_unterminated(&raw_line) {
+                let Some((index, next_line)) = lines.next() else {
+                    break;
+                };
+
+                last_line_number = index + 1;
+                let raw_line = raw_line.to_mut();
+                raw_line.push('\n');
+                raw_line.push_str(Self::read_line(next_line.as_ref(), last_line_number)?);
+            }
+
+            let line = self.strip_inline_comment(raw_line.trim());
+
+            if line.is_empty() {
                 continue;
             }
 
+            if let Some((key_line_number, key)) = bare_key.take() {
+                Self::emit_bare_key(f, key_line_number, &key, line.starts_with('{'));
+            }
+
+            // A closing brace belongs to the same level as the node it closes.
+            if line.starts_with('}') {
+                if depth == 0 {
+                    Self::tolerate(&mut errors, line_number, "closing brace without a node");
+                }
+
+                depth = depth.saturating_sub(1);
+            }
+
+            if let Some(message) = self.indentation_error(&raw_line, depth) {
+                Self::report(&mut errors, line_number, message)?;
+            }
+
+            if self.is_unterminated(line) {
+                Self::tolerate(&mut errors, line_number, "unterminated quotation mark");
+            }
+
+            // An opening brace holds no meaning for the parser, apart from increasing the depth.
+            // Right after a value, it opens a node that has both values and children.
+            if line.starts_with('{') {
+                depth += 1;
+
+                if let Some(key) = value_key.take() {
+                    f(line_number, ParseEvent::NodeStart(&key), &[]);
+                }
+
+                continue;
+            }
+
+            value_key = None;
+
             if line.starts_with('#') {
-                let comment_node = Self::construct_comment_node(parent_node.borrow_mut());
-                parent_node.push_object(line, comment_node);
+                if self.preserve_comments {
+                    f(line_number, ParseEvent::Comment(line), &[]);
+                }
+
                 continue;
             }
 
             // A closing brace means we're done with this node and can safely return to the parent.
             if line.starts_with('}') {
-                return Ok(());
+                f(line_number, ParseEvent::NodeEnd, &[]);
+                continue;
             }
 
-            // A line only containing text without any symbols marks a new node.
-            if !line.contains('=') {
-                let new_node = parent_node.get(line).borrow_mut();
-                return Self::read_inner(new_node, lines, line_number + skip);
-            }
+            // A line only containing text without any symbols marks a new node. The opening brace
+            // may also be placed on the same line, e.g. `node {`.
+            let Some((key, raw_value)) = self.split_assignment(line) else {
+                let (name, opens_block) = self.split_opening_brace(line);
+
+                if self.bare_flags && !opens_block {
+                    bare_key = Some((line_number, self.read_key(name).into_owned()));
+                    continue;
+                }
 
-            let split = line.split_once('=');
+                f(
+                    line_number,
+                    ParseEvent::NodeStart(&self.read_key(name)),
+                    &[],
+                );
+
+                if opens_block {
+                    depth += 1;
+                }
+
+                continue;
+            };
 
             // If there is an equal sign but no value, something went wrong. We just continue.
-            if let None | Some((_, "")) = split {
+            if raw_value.is_empty() {
                 continue;
             }
 
-            Self::parse_value_from_line(parent_node, split.unwrap());
+            let key = self.read_key(key.trim());
+            value_key = Some(key.to_string());
+            let (values, nulls) = self.parse_values(raw_value);
+            f(line_number, ParseEvent::Value { key: &key, values }, &nulls);
+        }
+
+        if let Some((key_line_number, key)) = bare_key {
+            Self::emit_bare_key(f, key_line_number, &key, false);
+        }
+
+        if depth > 0 {
+            Self::tolerate(
+                &mut errors,
+                last_line_number,
+                &format!("unexpected end of file, expected {depth} closing brace(s)"),
+            );
         }
 
         Ok(())
     }
 
-    fn parse_value_from_line(parent_node: &mut Datafile, (key, raw_value): (&str, &str)) {
+    /// Emits the event for a key on a line of its own, once it's known whether a block follows it.
+    /// Without a block, it's a flag.
+    fn emit_bare_key<F: FnMut(usize, ParseEvent<'_>, &[usize])>(
+        f: &mut F,
+        line_number: usize,
+        key: &str,
+        opens_block: bool,
+    ) {
+        if opens_block {
+            f(line_number, ParseEvent::NodeStart(key), &[]);
+        } else {
+            let values = vec!["true".to_string()];
+            f(line_number, ParseEvent::Value { key, values }, &[]);
+        }
+    }
+
+    /// Checks whether a line that isn't a comment contains a quotation mark that isn't closed.
+    fn is_unterminated(&self, line: &str) -> bool {
+        !line.trim_start().starts_with('#') && line.matches(self.quote_char).count() % 2 == 1
+    }
+
+    /// Removes a comment following the content of a line, if inline comments are enabled. Lines
+    /// consisting of a comment only are kept as they are.
+    fn strip_inline_comment<'l>(&self, line: &'l str) -> &'l str {
+        if !self.inline_comments || line.starts_with('#') {
+            return line;
+        }
+
+        let mut is_in_quotes = false;
+
+        for (index, char) in line.char_indices() {
+            if char == self.quote_char {
+                is_in_quotes = !is_in_quotes;
+            } else if char == '#' && !is_in_quotes {
+                return line[..index].trim_end();
+            }
+        }
+
+        line
+    }
+
+    /// Reports a syntax error. It's collected if a list for errors is given, otherwise it's
+    /// returned to stop scanning.
+    fn report(
+        errors: &mut Option<&mut Vec<DatafileError>>,
+        line: usize,
+        message: String,
+    ) -> std::io::Result<()> {
+        let error = DatafileError::Syntax { line, message };
+
+        match errors {
+            Some(errors) => {
+                errors.push(error);
+                Ok(())
+            }
+            None => Err(Error::new(ErrorKind::InvalidData, error)),
+        }
+    }
+
+    /// Reports a problem that is tolerated unless errors are collected.
+    fn tolerate(errors: &mut Option<&mut Vec<DatafileError>>, line: usize, message: &str) {
+        if let Some(errors) = errors {
+            errors.push(DatafileError::Syntax {
+                line,
+                message: message.to_string(),
+            });
+        }
+    }
+
+    /// Splits a list of values, returning them along with the indices of the ones that are null.
+    fn parse_values(&self, raw_value: &str) -> (Vec<String>, Vec<usize>) {
         let mut is_in_quotes = false;
-        let mut token_count = 0;
+        let mut values = vec![];
+        let mut nulls = vec![];
         let mut token = String::new();
+        let mut quoted = None;
 
         for char in raw_value.chars() {
-            // A token is delimited by quotation marks if it contains a list separator.
-            // It isn't added to the token itself. When serializing, the writer will handle
-            // it's insertion.
-            if char == '"' {
+            // A token is delimited by quotation marks if it contains a list separator, or
+            // whitespace that should be preserved. They aren't added to the token itself. When
+            // serializing, the writer will handle their insertion.
+            if char == self.quote_char {
                 is_in_quotes = !is_in_quotes;
+
+                // Remembers which part of the token was quoted, as it must not be trimmed.
+                let (start, _) = quoted.unwrap_or((token.len(), 0));
+                quoted = Some((start, token.len()));
                 continue;
             }
 
@@ -127,9 +949,8 @@ impl<'a> Reader<'a> {
             }
 
             // A list separator marks the end of a token, and the start of a new one.
-            if char == parent_node.list_separator {
-                Self::push_token_to_node(key, &token, token_count, parent_node);
-                token_count += 1;
+            if char == self.list_separator {
+                self.push_token(&mut values, &mut nulls, &token, quoted.take());
                 token.clear();
                 continue;
             }
@@ -137,32 +958,101 @@ impl<'a> Reader<'a> {
             token.push(char);
         }
 
-        if !token.is_empty() {
-            Self::push_token_to_node(key, &token, token_count, parent_node);
+        // The last token is pushed even if it's empty, so a trailing list separator keeps its
+        // empty slot, just like two consecutive separators do.
+        self.push_token(&mut values, &mut nulls, &token, quoted);
+        (values, nulls)
+    }
+
+    /// Adds a token to the values. An unquoted token matching the null token is stored as an
+    /// empty value, and its index is remembered.
+    fn push_token(
+        &self,
+        values: &mut Vec<String>,
+        nulls: &mut Vec<usize>,
+        token: &str,
+        quoted: Option<(usize, usize)>,
+    ) {
+        let value = self.trim_token(token, quoted);
+
+        if quoted.is_none() && self.null_token.as_deref() == Some(value.as_str()) {
+            nulls.push(values.len());
+            values.push(String::new());
+        } else {
+            values.push(value);
+        }
+    }
+
+    /// Trims the whitespace surrounding a token. The part that was quoted is left untouched,
+    /// unless trimming values is enabled.
+    fn trim_token(&self, token: &str, quoted: Option<(usize, usize)>) -> String {
+        let Some((start, end)) = quoted.filter(|_| !self.trim_values) else {
+            return token.trim().to_string();
+        };
+
+        let (before, rest) = token.split_at(start);
+        let (inner, after) = rest.split_at(end - start);
+
+        format!("{}{inner}{}", before.trim_start(), after.trim_end())
+    }
+
+    /// Splits a line at the first equal sign that isn't part of a quoted key.
+    fn split_assignment<'b>(&self, line: &'b str) -> Option<(&'b str, &'b str)> {
+        let mut is_in_quotes = false;
+
+        for (index, char) in line.char_indices() {
+            if char == self.quote_char {
+                is_in_quotes = !is_in_quotes;
+            } else if !is_in_quotes && line[index..].starts_with(&self.assignment_token) {
+                return Some((&line[..index], &line[index + self.assignment_token.len()..]));
+            }
+        }
+
+        None
+    }
+
+    /// Splits an opening brace off the end of a node name, unless it's part of a quoted name.
+    /// Returns the name, and whether a brace was found.
+    fn split_opening_brace<'b>(&self, line: &'b str) -> (&'b str, bool) {
+        match line.strip_suffix('{') {
+            Some(name) if name.matches(self.quote_char).count() % 2 == 0 => (name.trim_end(), true),
+            _ => (line, false),
+        }
+    }
+
+    /// Unquotes a key and passes it through the key transform, if there is one.
+    fn read_key<'b>(&self, key: &'b str) -> Cow<'b, str> {
+        let key = self.unquote_key(key);
+
+        match &self.key_transform {
+            Some(transform) => Cow::Owned(transform.apply(key)),
+            None => Cow::Borrowed(key),
         }
     }
 
+    /// Removes the quotation marks the writer puts around keys containing reserved characters.
     #[inline]
-    fn push_token_to_node(key: &str, token: &str, index: usize, node: &mut Datafile) {
-        let (key, token) = (key.trim(), token.trim());
-        node.get(key).set_string(token, index);
+    fn unquote_key<'b>(&self, key: &'b str) -> &'b str {
+        key.strip_prefix(self.quote_char)
+            .and_then(|key| key.strip_suffix(self.quote_char))
+            .unwrap_or(key)
     }
 
-    fn construct_comment_node(parent_node: &Datafile) -> Datafile {
-        let mut comment_node = Datafile::new(
-            Some(parent_node.list_separator),
-            Some(&*parent_node.whitespace_sequence),
-        );
+    /// Compares the indentation of a line against the depth it's located at, if strict
+    /// indentation is enabled. Returns an error message if they don't match.
+    fn indentation_error(&self, line: &str, depth: usize) -> Option<String> {
+        let sequence = self.indentation.as_ref()?;
+        let indentation = &line[..line.len() - line.trim_start().len()];
 
-        comment_node.is_comment = true;
-        comment_node
+        (indentation != sequence.repeat(depth))
+            .then(|| format!("expected an indentation of {depth} level(s)"))
     }
 
-    fn trim_line<'b>(
+    fn read_line<'b>(
         line: Result<&'b String, &Error>,
         line_number: usize,
     ) -> Result<&'b str, Error> {
-        line.map(|line| line.trim()).map_err(|error| {
+        line.map(String::as_str).map_err(|error| {
             Error::new(
                 error.kind(),
                 format!("Error reading line {line_number}: {}", error),
@@ -170,3 +1060,842 @@ impl<'a> Reader<'a> {
         })
     }
 }
+
+/// Decides which parse events are needed to build the node at a path. Branches whose name doesn't
+/// match the path are skipped entirely, by tracking the depth of the nodes in the file.
+struct PathFilter<'p> {
+    names: Vec<&'p str>,
+    depth: usize,
+    /// The depth of the branch that is currently being skipped, if any.
+    skip_until: Option<usize>,
+}
+
+impl PathFilter<'_> {
+    fn accepts(
+        &mut self,
+        event: &ParseEvent<'_>,
+        names_match: impl Fn(&str, &str) -> bool,
+    ) -> bool {
+        let on_path = |name: &str| {
+            self.names
+                .get(self.depth)
+                .map_or(true, |segment| names_match(name, segment))
+        };
+
+        match event {
+            ParseEvent::NodeStart(name) => {
+                let accepted = self.skip_until.is_none() && on_path(name);
+                if !accepted && self.skip_until.is_none() {
+                    self.skip_until = Some(self.depth);
+                }
+
+                self.depth += 1;
+                accepted
+            }
+            ParseEvent::NodeEnd => {
+                self.depth = self.depth.saturating_sub(1);
+
+                if self.skip_until == Some(self.depth) {
+                    self.skip_until = None;
+                    return false;
+                }
+
+                self.skip_until.is_none()
+            }
+            ParseEvent::Value { key, .. } => self.skip_until.is_none() && on_path(key),
+            ParseEvent::Comment(_) => self.skip_until.is_none(),
+        }
+    }
+}
+
+/// Turns parse events into a tree of datafiles. Nodes that are still open are kept on a stack of
+/// owned datafiles and attached to their parent once they end, so no references into the tree
+/// have to be kept around.
+struct TreeBuilder<'a> {
+    top_node: &'a mut Datafile,
+    stack: Vec<(Arc<str>, Datafile)>,
+    record_source_lines: bool,
+    /// The interner keys are taken from, if `ReadOptions::intern_keys` is enabled.
+    interner: Option<Interner>,
+    /// Whether the previous event was a value, whose node may be reopened by a `NodeStart`.
+    after_value: bool,
+    /// Consecutive comments that will be attached to the node following them directly, along
+    /// with the line they were found on.
+    pending_comments: Vec<(usize, String)>,
+    /// Whether standalone comments directly below each other form a single comment node.
+    group_comments: bool,
+    /// How the values of a key appearing more than once in a node are handled.
+    duplicate_keys: DuplicateKeys,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn new(top_node: &'a mut Datafile, record_source_lines: bool) -> Self {
+        Self {
+            top_node,
+            stack: vec![],
+            record_source_lines,
+            interner: None,
+            after_value: false,
+            pending_comments: vec![],
+            group_comments: false,
+            duplicate_keys: DuplicateKeys::Overwrite,
+        }
+    }
+
+    /// Returns the node that is currently being parsed.
+    fn current(&mut self) -> &mut Datafile {
+        match self.stack.last_mut() {
+            Some((_, node)) => node,
+            None => self.top_node,
+        }
+    }
+
+    fn handle(&mut self, line_number: usize, event: ParseEvent<'_>, nulls: &[usize]) {
+        let source_line = self.record_source_lines.then_some(line_number);
+        let after_value = std::mem::replace(
+            &mut self.after_value,
+            matches!(event, ParseEvent::Value { .. }),
+        );
+
+        let doc_comment = match event {
+            ParseEvent::NodeStart(_) | ParseEvent::Value { .. } => {
+                self.take_doc_comment(line_number)
+            }
+            ParseEvent::NodeEnd => {
+                self.flush_comments();
+                None
+            }
+            ParseEvent::Comment(_) => None,
+        };
+
+        match event {
+            ParseEvent::NodeStart(name) => {
+                if after_value {
+                    if let Some(node) = self.reopen_last(name) {
+                        self.stack.push(node);
+                        return;
+                    }
+                }
+
+                let mut node = self.current().new_child();
+                node.source_line = source_line;
+                node.doc_comment = doc_comment;
+                let name = match &mut self.interner {
+                    Some(interner) => interner.intern(name),
+                    None => Arc::from(name),
+                };
+                self.stack.push((name, node));
+            }
+            // A closing brace without a matching node holds no meaning. We just continue.
+            ParseEvent::NodeEnd => self.close_node(),
+            ParseEvent::Value { key, values } => {
+                let parent = match self.stack.last_mut() {
+                    Some((_, node)) => node,
+                    None => &mut *self.top_node,
+                };
+                let node = parent.entry_interned(key, self.interner.as_mut());
+                node.source_line = node.source_line.or(source_line);
+                node.doc_comment = doc_comment.or(node.doc_comment.take());
+
+                let offset = match self.duplicate_keys {
+                    DuplicateKeys::Overwrite => 0,
+                    DuplicateKeys::Append => node.contents.len(),
+                };
+
+                for (index, value) in values.iter().enumerate() {
+                    node.set_string(value, offset + index);
+                }
+
+                for &index in nulls {
+                    node.set_null(offset + index);
+                }
+            }
+            ParseEvent::Comment(text) => {
+                // A comment that isn't directly below the previous one starts a new group.
+                if matches!(self.pending_comments.last(), Some((line, _)) if line + 1 != line_number)
+                {
+                    self.flush_comments();
+                }
+
+                self.pending_comments.push((line_number, text.to_string()));
+            }
+        }
+    }
+
+    /// Returns the pending comments as a doc comment, if they directly precede the given line.
+    /// Otherwise they are kept as standalone comments.
+    fn take_doc_comment(&mut self, line_number: usize) -> Option<String> {
+        match self.pending_comments.last() {
+            Some((line, _)) if line + 1 == line_number => {}
+            _ => {
+                self.flush_comments();
+                return None;
+            }
+        }
+
+        let lines: Vec<_> = self
+            .pending_comments
+            .drain(..)
+            .map(|(_, text)| text.strip_prefix('#').unwrap_or(&text).to_string())
+            .collect();
+
+        Some(lines.join("\n"))
+    }
+
+    /// Adds the pending comments to the current node as standalone comments. If they're grouped,
+    /// they're added as a single comment, recorded with the line of the first one.
+    fn flush_comments(&mut self) {
+        let record_source_lines = self.record_source_lines;
+        let mut comments = std::mem::take(&mut self.pending_comments);
+
+        if self.group_comments && comments.len() > 1 {
+            let line_number = comments[0].0;
+            let lines: Vec<_> = comments.drain(..).map(|(_, text)| text).collect();
+            comments.push((line_number, lines.join("\n")));
+        }
+
+        let parent = self.current();
+
+        for (line_number, text) in comments {
+            let mut comment_node = parent.new_child();
+            comment_node.is_comment = true;
+            comment_node.source_line = record_source_lines.then_some(line_number);
+            parent.push_object(text, comment_node);
+        }
+    }
+
+    /// Attaches the node that is currently being parsed to its parent. Sibling nodes sharing a
+    /// name are kept apart instead of being merged into one another.
+    fn close_node(&mut self) {
+        if let Some((name, node)) = self.stack.pop() {
+            self.current().push_child(name, node);
+        }
+    }
+
+    /// Detaches the last child of the current node again, if it has the given name and no
+    /// children yet, so a block following its values can be added to it.
+    fn reopen_last(&mut self, name: &str) -> Option<(Arc<str>, Datafile)> {
+        let parent = self.current();
+        let (last_name, last) = parent.object_vec.last()?;
+
+        if last.is_comment || !last.object_vec.is_empty() || !parent.names_match(last_name, name) {
+            return None;
+        }
+
+        parent.pop_child()
+    }
+
+    /// Closes all nodes that are still open at the end of the file.
+    fn finish(mut self) {
+        self.flush_comments();
+
+        while !self.stack.is_empty() {
+            self.close_node();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_path;
+
+    fn read_str(datafile: &mut Datafile, text: &str) {
+        Reader::new(datafile).read_str(text).unwrap();
+    }
+
+    #[test]
+    fn test_read_after_nested_node() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(
+            &mut datafile,
+            "a\n{\n\tb\n\t{\n\t\tx = 1\n\t}\n\ty = 2\n}\nc = 3\n",
+        );
+
+        assert_eq!(datafile.get("a").get("b").get("x").get_integer(0), 1);
+        assert_eq!(datafile.get("a").get("y").get_integer(0), 2);
+        assert_eq!(datafile.get("c").get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_read_duplicate_siblings() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(
+            &mut datafile,
+            "enemy\n{\n\tname = orc\n}\nenemy\n{\n\tname = goblin\n}\n",
+        );
+
+        let enemies = datafile.get_all("enemy");
+        assert_eq!(enemies.len(), 2);
+        assert_eq!(datafile.get("enemy").get("name").get_string(0), "orc");
+
+        let names: Vec<_> = datafile
+            .get_all("enemy")
+            .into_iter()
+            .map(|enemy| enemy.object_vec[0].1.get_string(0))
+            .collect();
+        assert_eq!(names, ["orc", "goblin"]);
+    }
+
+    #[test]
+    fn test_read_quoted_keys() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(
+            &mut datafile,
+            "\"a=b\" = 1\n\"#not a comment\" = 2\n\"{block}\"\n{\n\tx = 3\n}\n",
+        );
+
+        assert_eq!(datafile.get("a=b").get_integer(0), 1);
+        assert_eq!(datafile.get("#not a comment").get_integer(0), 2);
+        assert_eq!(datafile.get("{block}").get("x").get_integer(0), 3);
+        assert!(datafile.object_vec.iter().all(|(_, node)| !node.is_comment));
+    }
+
+    #[test]
+    fn test_read_empty_list_values() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(
+            &mut datafile,
+            "middle = a,,c\ntrailing = a,b,\nspaced = a, , c\n",
+        );
+
+        let middle = datafile.get("middle");
+        assert_eq!(middle.get_value_count(), 3);
+        assert_eq!(middle.get_string(1), "");
+        assert_eq!(middle.get_string(2), "c");
+
+        let trailing = datafile.get("trailing");
+        assert_eq!(trailing.get_value_count(), 3);
+        assert_eq!(trailing.get_string(1), "b");
+        assert_eq!(trailing.get_string(2), "");
+
+        assert_eq!(datafile.get("spaced").get_value_count(), 3);
+    }
+
+    #[test]
+    fn test_read_overwrites_existing_data() {
+        let mut datafile = Datafile::new(Some(';'), None);
+        datafile.get("stale").set_string("value", 0);
+
+        read_str(&mut datafile, "node\n{\n\tlist = a;b\n}\n");
+        read_str(&mut datafile, "node\n{\n\tlist = c;d\n}\n");
+
+        assert!(!datafile.has_property("stale"));
+        assert_eq!(datafile.get_all("node").len(), 1);
+        assert_eq!(datafile.get("node").get("list").get_string(0), "c");
+        assert_eq!(datafile.list_separator, ';');
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let mut datafile = Datafile::new(None, None);
+        let reader = Reader::new(&mut datafile);
+
+        let mut events = vec![];
+        reader
+            .parse_events(
+                "# comment\nnode\n{\n\tlist = a, \"b, c\"\n}\nflat = 1\n".as_bytes(),
+                |event| events.push(format!("{:?}", event)),
+            )
+            .unwrap();
+
+        assert_eq!(
+            events,
+            [
+                format!("{:?}", ParseEvent::Comment("# comment")),
+                format!("{:?}", ParseEvent::NodeStart("node")),
+                format!(
+                    "{:?}",
+                    ParseEvent::Value {
+                        key: "list",
+                        values: vec!["a".to_string(), "b, c".to_string()],
+                    }
+                ),
+                format!("{:?}", ParseEvent::NodeEnd),
+                format!(
+                    "{:?}",
+                    ParseEvent::Value {
+                        key: "flat",
+                        values: vec!["1".to_string()],
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_unbalanced_braces() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, "}\na = 1\nnode\n{\n\tb = 2\n");
+
+        assert_eq!(datafile.get("a").get_integer(0), 1);
+        assert_eq!(datafile.get("node").get("b").get_integer(0), 2);
+    }
+
+    #[test]
+    fn test_read_indexed_property() {
+        let mut datafile = Datafile::new(None, None);
+        read_str(
+            &mut datafile,
+            "inventory\n{\n\titem\n\t{\n\t\tname = sword\n\t}\n\titem\n\t{\n\t\tname = shield\n\t}\n}\n",
+        );
+
+        let item = datafile.get_indexed_property("inventory.item", 1);
+        assert_eq!(item.try_get("name").unwrap().get_string(0), "shield");
+
+        let name = datafile.get_property("inventory.item[0].name");
+        assert_eq!(name.get_string(0), "sword");
+    }
+
+    #[test]
+    fn test_read_quoted_whitespace() {
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.trim_values = false;
+        reader
+            .read_str("list = \"  padded  \" , plain ,\" a, b \"\nempty = \"\"\n")
+            .unwrap();
+
+        let list = datafile.get("list");
+        assert_eq!(list.get_value_count(), 3);
+        assert_eq!(list.get_string(0), "  padded  ");
+        assert_eq!(list.get_string(1), "plain");
+        assert_eq!(list.get_string(2), " a, b ");
+
+        assert_eq!(datafile.get("empty").get_value_count(), 1);
+        assert_eq!(datafile.get("empty").get_string(0), "");
+    }
+
+    #[test]
+    fn test_read_untrimmed_values() {
+        let text = "name = Javid\nlist = a, b\npadded =  \"  x  \" , \" y\"\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.trim_values = false;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.get("name").get_string(0), "Javid");
+        assert_eq!(datafile.get("list").get_string_list(), ["a", "b"]);
+        assert_eq!(datafile.get("padded").get_string_list(), ["  x  ", " y"]);
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get("name").get_string(0), "Javid");
+        assert_eq!(datafile.get("list").get_string_list(), ["a", "b"]);
+        assert_eq!(datafile.get("padded").get_string_list(), ["x", "y"]);
+    }
+
+    #[test]
+    fn test_read_invalid_utf8() {
+        let bytes: &[u8] = b"name = caf\xE9\r\nother = 1\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let error = Reader::new(&mut datafile).read_from(bytes).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 1"));
+
+        let mut datafile = Datafile::new(None, None);
+        Reader::new(&mut datafile).read_bytes_lossy(bytes).unwrap();
+        assert_eq!(datafile.get("name").get_string(0), "caf\u{FFFD}");
+        assert_eq!(datafile.get("other").get_integer(0), 1);
+    }
+
+    #[test]
+    fn test_read_max_line_length() {
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.max_line_length = 8;
+        reader.read_str("a = 1234\nb = 2\n").unwrap();
+
+        let error = reader
+            .read_str("a = 1\nb = 123456789\nc = 3\n")
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "Error reading line 2: line exceeds the maximum length of 8 bytes"
+        );
+    }
+
+    #[test]
+    fn test_read_max_total_bytes() {
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.max_total_bytes = 12;
+        reader.read_str("a = 1\nb = 2\n").unwrap();
+
+        let error = reader.read_str("a = 1\nb = 2\nc = 3\n").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "Error reading line 3: input exceeds the maximum size of 12 bytes"
+        );
+    }
+
+    #[test]
+    fn test_read_same_line_braces() {
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.strict_indentation = true;
+        reader
+            .read_str(
+                "node {\n\tchild{\n\t\tx = 1\n\t}\n\ty = 2\n}\n\"quoted {\" {\n\tz = 3\n}\nw = 4\n",
+            )
+            .unwrap();
+
+        assert_eq!(datafile.get_property("node.child.x").get_integer(0), 1);
+        assert_eq!(datafile.get_property("node.y").get_integer(0), 2);
+        assert_eq!(datafile.get("quoted {").get("z").get_integer(0), 3);
+        assert_eq!(datafile.get("w").get_integer(0), 4);
+        assert_eq!(datafile.child_count(), 3);
+    }
+
+    #[test]
+    fn test_read_recovering() {
+        let text = "a = 1\n}\nnode\n{\nb = \"unterminated\n\tc = 3\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.strict_indentation = true;
+
+        let errors = reader.read_recovering(text.as_bytes()).unwrap();
+        assert_eq!(
+            errors,
+            [
+                DatafileError::Syntax {
+                    line: 2,
+                    message: "closing brace without a node".to_string()
+                },
+                DatafileError::Syntax {
+                    line: 5,
+                    message: "expected an indentation of 1 level(s)".to_string()
+                },
+                DatafileError::Syntax {
+                    line: 5,
+                    message: "unterminated quotation mark".to_string()
+                },
+                DatafileError::Syntax {
+                    line: 6,
+                    message: "unexpected end of file, expected 1 closing brace(s)".to_string()
+                },
+            ]
+        );
+
+        // The tree is built as far as possible.
+        assert_eq!(datafile.get("a").get_integer(0), 1);
+        assert_eq!(
+            datafile.get_property("node.b").get_string(0),
+            "unterminated"
+        );
+        assert_eq!(datafile.get_property("node.c").get_integer(0), 3);
+
+        // The problems that are tolerated otherwise don't fail a regular read.
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get_property("node.c").get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_read_byte_order_mark() {
+        let path = temp_path("byte_order_mark.txt");
+        std::fs::write(&path, "\u{FEFF}name = Javid\nnode\n{\n}\n").unwrap();
+
+        let mut datafile = Datafile::new(None, None);
+        let result = Reader::new(&mut datafile).read(&path);
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+
+        assert!(datafile.has_property("name"));
+        assert_eq!(datafile.get("name").get_string(0), "Javid");
+
+        // Only a mark at the very start is skipped.
+        read_str(&mut datafile, "a = 1\n\u{FEFF}b = 2\n");
+        assert!(datafile.has_property("\u{FEFF}b"));
+    }
+
+    #[test]
+    fn test_read_bare_flags() {
+        let text = "fullscreen\nvsync\n\n# comment\nnode\n\n{\n\tdebug\n\tchild {\n\t}\n}\nlast\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.bare_flags = true;
+        reader.options.record_source_lines = true;
+        reader.read_str(text).unwrap();
+
+        assert!(datafile.get("fullscreen").get_bool(0));
+        assert!(datafile.get("vsync").get_bool(0));
+        assert_eq!(datafile.get("vsync").source_line(), Some(2));
+        assert!(datafile.get("last").get_bool(0));
+
+        let node = datafile.get("node");
+        assert_eq!(node.get_value_count(), 0);
+        assert_eq!(node.source_line(), Some(5));
+        assert!(node.get("debug").get_bool(0));
+        assert!(node.get("child").is_empty());
+
+        // By default, every bare key starts a node.
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get("fullscreen").get_value_count(), 0);
+        assert!(datafile.get("fullscreen").has_property("vsync"));
+    }
+
+    #[test]
+    fn test_read_key_transform() {
+        let text = "@name = Javid\n@pc {\n\t@ram = 32\n\t\"@gpu\"\n\t{\n\t}\n}\nplain = 1\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.key_transform = Some(KeyTransform::new(|key| {
+            key.strip_prefix('@').unwrap_or(key).to_string()
+        }));
+        reader.read_str(text).unwrap();
+
+        let options = reader.options.clone();
+        assert_eq!(options, reader.options);
+        assert_ne!(options, ReadOptions::default());
+
+        assert_eq!(datafile.get("name").get_string(0), "Javid");
+        assert_eq!(datafile.get("pc").get("ram").get_integer(0), 32);
+        assert!(datafile.get("pc").has_property("gpu"));
+        assert_eq!(datafile.get("plain").get_integer(0), 1);
+        assert!(!datafile.has_property("@name"));
+    }
+
+    #[test]
+    fn test_read_duplicate_keys() {
+        let text = "name = Javid\nlist = a, b, c\nnode\n{\n\tx = 1\n\tx = ~\n}\nname = Velican\nlist = d\n";
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get("name").get_string_list(), ["Velican"]);
+        assert_eq!(datafile.get("list").get_string_list(), ["d", "b", "c"]);
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.duplicate_keys = DuplicateKeys::Append;
+        reader.options.null_token = Some("~".to_string());
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.get("name").get_string_list(), ["Javid", "Velican"]);
+        assert_eq!(datafile.get("list").get_string_list(), ["a", "b", "c", "d"]);
+        let x = datafile.get("node").get("x");
+        assert_eq!(x.get_value_count(), 2);
+        assert!(!x.is_null(0));
+        assert!(x.is_null(1));
+        assert_eq!(datafile.get("node").object_vec.len(), 1);
+    }
+
+    #[test]
+    fn test_read_without_comments() {
+        let text =
+            "# header\n\n# doc\nname = Javid\npc\n{\n\t# nested\n\tram = 32\n}\n# trailing\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.preserve_comments = false;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.object_vec.len(), 2);
+        assert_eq!(datafile.comment_count(), 0);
+        assert_eq!(datafile.get("name").doc_comment(), None);
+        assert_eq!(datafile.get("pc").object_vec.len(), 1);
+        assert_eq!(datafile.get("pc").get("ram").get_integer(0), 32);
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.comment_count(), 2);
+        assert_eq!(datafile.get("name").doc_comment(), Some(" doc"));
+    }
+
+    #[test]
+    fn test_read_grouped_comments() {
+        let text = "# first\n# second\n# third\n\npc\n{\n\tram = 32\n\t# nested\n\t# block\n}\n\n# single\n\nname = Javid\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.group_comments = true;
+        reader.options.record_source_lines = true;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.comment_count(), 2);
+        let (block, node) = &datafile.object_vec[0];
+        assert_eq!(&**block, "# first\n# second\n# third");
+        assert_eq!(node.source_line(), Some(1));
+        assert_eq!(&*datafile.get("pc").object_vec[1].0, "# nested\n# block");
+        assert_eq!(&*datafile.object_vec[2].0, "# single");
+
+        // Every line of a block is written with its own indentation.
+        let written = datafile.write_to_string_with(&crate::WriteOptions::default());
+        assert_eq!(
+            written,
+            "# first\n# second\n# third\n\npc\n{\n\tram = 32\n\t# nested\n\t# block\n}\n# single\nname = Javid\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.group_comments = true;
+        reader.read_str(&written).unwrap();
+        assert_eq!(result.object_vec[0], datafile.object_vec[0]);
+        assert_eq!(result.get("pc"), datafile.get("pc"));
+
+        // By default, every line is a comment of its own.
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.comment_count(), 4);
+    }
+
+    #[test]
+    fn test_read_detect_indentation() {
+        let text = "a = 1\n\nnode\n{\n\tb = 2\n\n\tchild\n\t{\n\t\tc = 3\n\t}\n}\n";
+
+        let mut datafile = Datafile::new(None, Some("    "));
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.detect_indentation = true;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.whitespace_sequence, "\t");
+        assert_eq!(
+            datafile.write_to_string_with(&crate::WriteOptions::default()),
+            text
+        );
+
+        // Without indented lines, the configured sequence is kept.
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.detect_indentation = true;
+        reader.read_str("a = 1\n").unwrap();
+        assert_eq!(datafile.whitespace_sequence, "\t");
+
+        let mut datafile = Datafile::new(None, Some("    "));
+        Reader::new(&mut datafile).read_str(text).unwrap();
+        assert_eq!(datafile.whitespace_sequence, "    ");
+    }
+
+    #[test]
+    fn test_read_strict_indentation() {
+        let valid = "a = 1\nnode\n{\n\t# comment\n\tb = 2\n\n\tchild\n\t{\n\t\tc = 3\n\t}\n}\n";
+        let invalid = "node\n{\n\tb = 2\nc = 3\n}\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.strict_indentation = true;
+        reader.read_str(valid).unwrap();
+
+        let error = reader.read_str(invalid).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "Error reading line 4: expected an indentation of 1 level(s)"
+        );
+
+        // Indentation is ignored unless strict indentation is enabled.
+        read_str(&mut datafile, invalid);
+        assert_eq!(datafile.get("node").get("c").get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_read_source_lines() {
+        let text = "# comment\n\nnode\n{\n\tname = Javid\n\n\tname = Javid, 24\n}\n";
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get("node").source_line(), None);
+
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.record_source_lines = true;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.source_line(), None);
+        assert_eq!(datafile.object_vec[0].1.source_line(), Some(1));
+        assert_eq!(datafile.get("node").source_line(), Some(3));
+        assert_eq!(datafile.get_property("node.name").source_line(), Some(5));
+    }
+
+    #[test]
+    fn test_read_interned_keys() {
+        let text = "entity\n{\n\tx = 1\n\ty = 2\n}\nentity\n{\n\tx = 3\n\ty = 4\n}\n";
+        let first_key = |datafile: &Datafile, index: usize| {
+            Arc::clone(&datafile.object_vec[index].1.object_vec[0].0)
+        };
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert!(!Arc::ptr_eq(
+            &first_key(&datafile, 0),
+            &first_key(&datafile, 1)
+        ));
+
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.intern_keys = true;
+        reader.read_str(text).unwrap();
+
+        assert!(Arc::ptr_eq(
+            &first_key(&datafile, 0),
+            &first_key(&datafile, 1)
+        ));
+        assert!(Arc::ptr_eq(
+            &datafile.object_vec[0].0,
+            &datafile.object_vec[1].0
+        ));
+        assert_eq!(datafile.get_nth_mut("entity", 1).get("x").get_integer(0), 3);
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut datafile =
+            Reader::parse("# comment\n\nname = Javid, 24\nnode\n{\n\tx = 1\n}\n").unwrap();
+
+        assert_eq!(datafile.get("name").get_string(0), "Javid");
+        assert_eq!(datafile.get("name").get_integer(1), 24);
+        assert_eq!(datafile.get("node").get("x").get_integer(0), 1);
+        assert_eq!(datafile.comment_count(), 1);
+    }
+
+    #[test]
+    fn test_read_subtree() {
+        let text = "settings\n{\n\tinventory\n\t{\n\t\tgold = 1\n\t}\n}\n\
+                    world\n{\n\tenemy\n\t{\n\t\thp = 5\n\t}\n\
+                    \tplayer\n{\n\t\tname = Javid\n\t\tinventory\n\t\t{\n\
+                    \t\t\tgold = 100\n\t\t\tsword\n\t\t\t{\n\t\t\t\tdamage = 7\n\
+                    \t\t\t}\n\t\t}\n\t}\n\tenemy\n\t{\n\t\thp = 9\n\t}\n}\n";
+
+        let mut datafile = Datafile::new(None, None);
+        let reader = Reader::new(&mut datafile);
+        let subtree = |dotted| reader.subtree_from(reader.lines(text.as_bytes(), false), dotted);
+
+        let mut inventory = subtree("world.player.inventory").unwrap();
+        assert_eq!(inventory.get("gold").get_integer(0), 100);
+        assert_eq!(inventory.get("sword").get("damage").get_integer(0), 7);
+        assert_eq!(inventory.child_count(), 2);
+
+        assert_eq!(
+            subtree("world.enemy[1]").unwrap().get("hp").get_integer(0),
+            9
+        );
+        assert_eq!(subtree("world.player.name").unwrap().get_string(0), "Javid");
+
+        let error = subtree("world.player.armor").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(datafile.is_empty());
+    }
+
+    #[test]
+    fn test_read_inline_comments() {
+        let text = "x = 5 # note\nname = \"a#b\", c # \"quoted\"\nnode # block\n{\n\ty = 1\n}\n";
+
+        let mut datafile = Datafile::new(None, None);
+        read_str(&mut datafile, text);
+        assert_eq!(datafile.get("x").get_string(0), "5 # note");
+
+        let mut reader = Reader::new(&mut datafile);
+        reader.options.inline_comments = true;
+        reader.read_str(text).unwrap();
+
+        assert_eq!(datafile.get("x").get_integer(0), 5);
+        assert_eq!(datafile.get("name").get_string(0), "a#b");
+        assert_eq!(datafile.get("name").get_string(1), "c");
+        assert_eq!(datafile.get("node").get("y").get_integer(0), 1);
+        assert_eq!(datafile.comment_count(), 0);
+    }
+}
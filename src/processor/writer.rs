@@ -1,5 +1,8 @@
-use std::fs::File;
-use std::io::Write;
+use std::borrow::Cow;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::datafile::Datafile;
 
@@ -21,15 +24,190 @@ use crate::datafile::Datafile;
 #[derive(Debug)]
 pub struct Writer<'a> {
     pub data_file: &'a Datafile,
+    /// The options controlling how the datafile is formatted.
+    pub options: WriteOptions,
     buffer: String,
+    truncation: Option<Truncation>,
+}
+
+/// The size in bytes the output is written in when it's streamed, see `Writer::write_into`.
+const STREAM_CHUNK_SIZE: usize = 1 << 16;
+
+/// The destination output is streamed to while a datafile is traversed, instead of collecting it
+/// in the buffer. Once writing fails, the error is kept and everything else is skipped.
+#[derive(Default)]
+struct Stream<'s> {
+    output: Option<&'s mut dyn Write>,
+    /// Whether anything has been written yet, so the start of the output can still be adjusted.
+    started: bool,
+    error: Option<std::io::Error>,
+}
+
+impl Stream<'_> {
+    /// Writes the buffer to the output once it has grown beyond the chunk size. Its last
+    /// character is kept back, as it may be a line break that must be removed at the end.
+    fn flush(&mut self, buffer: &mut String, options: &WriteOptions) {
+        if self.output.is_none() || buffer.len() < STREAM_CHUNK_SIZE {
+            return;
+        }
+
+        let last = buffer
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index);
+        self.write(&buffer[..last], options);
+        buffer.replace_range(..last, "");
+    }
+
+    /// Writes a chunk to the output, applying the options that concern the output as a whole
+    /// the same way `Writer::finish` does.
+    fn write(&mut self, chunk: &str, options: &WriteOptions) {
+        let (Some(output), None) = (self.output.as_mut(), &self.error) else {
+            return;
+        };
+
+        let mut chunk = chunk;
+        let mut result = Ok(());
+
+        if !self.started {
+            self.started = true;
+            chunk = chunk.strip_prefix('\n').unwrap_or(chunk);
+
+            if options.byte_order_mark {
+                result = output.write_all("\u{FEFF}".as_bytes());
+            }
+        }
+
+        result = result.and_then(|()| {
+            if options.crlf {
+                output.write_all(chunk.replace('\n', "\r\n").as_bytes())
+            } else {
+                output.write_all(chunk.as_bytes())
+            }
+        });
+
+        self.error = result.err();
+    }
+}
+
+/// Limits how much of a tree is written, used for the `Debug` output of a datafile. Omitted
+/// parts are replaced by a line with the number of nodes they contain.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Truncation {
+    /// The number of nested blocks written before their contents are omitted.
+    pub(crate) max_depth: usize,
+    /// The number of entries written per node before the remaining ones are omitted.
+    pub(crate) max_entries: usize,
+}
+
+/// Options controlling how a datafile is formatted when it's written. The defaults reproduce the
+/// format of the original implementation.
+///
+/// # Examples
+///
+/// ```
+/// # use olc_datafile_rust::{Datafile, WriteOptions};
+/// let mut datafile = Datafile::new(None, None);
+/// datafile.get("b").set_integer(1, 0);
+/// datafile.get("a").set_integer(2, 0);
+///
+/// let options = WriteOptions {
+///     sort_keys: true,
+///     trailing_newline: false,
+///     ..WriteOptions::default()
+/// };
+///
+/// assert_eq!(datafile.write_to_string_with(&options), "a = 2\nb = 1");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Whether to write child nodes sorted by name instead of in insertion order, which makes the
+    /// output reproducible regardless of the order the nodes were built in. Values keep their
+    /// order, and comments stay in front of the node following them. Defaults to `false`.
+    pub sort_keys: bool,
+    /// Whether to align the `=` signs of sibling values in a column, by padding shorter keys with
+    /// spaces. Only values without children are aligned. Defaults to `false`.
+    pub align_equals: bool,
+    /// The character to group the digits of large integers with, e.g. `,` for `1,000,000`. Only
+    /// nodes holding a single integer are grouped, and the result is delimited by quotation
+    /// marks, so it isn't split at the list separator. Only `,` and `_` are supported, as
+    /// `get_integer` reads values grouped by them back, other characters leave values ungrouped.
+    /// Defaults to `None`.
+    pub digit_grouping: Option<char>,
+    /// Whether to put a space after every list separator, e.g. `a, b` instead of `a,b`. Defaults
+    /// to `true`.
+    pub space_after_separator: bool,
+    /// Whether the last line ends with a line break. Defaults to `true`.
+    pub trailing_newline: bool,
+    /// Whether to end lines with `\r\n` instead of `\n`. Defaults to `false`.
+    pub crlf: bool,
+    /// The value null values are written as, e.g. `~`, see `Datafile::is_null`. Other values
+    /// that happen to be equal to it are delimited by quotation marks, so they aren't read back
+    /// as null. Defaults to `None`, which writes null values as empty ones.
+    pub null_token: Option<String>,
+    /// The number of decimals to write reals with, e.g. `2` for `1.88` instead of `1.8800001`.
+    /// Only values set with `set_real` or `set_real_list` are rounded. Values set as strings or
+    /// read from a file are left as they are, even if they look like reals. Defaults to `None`,
+    /// which writes values unchanged.
+    pub real_precision: Option<usize>,
+    /// Whether to start the output with a UTF-8 byte order mark, which some tools require. The
+    /// reader skips it. `append` only writes it to files that are empty. Defaults to `false`.
+    pub byte_order_mark: bool,
+    /// The token between keys and values, e.g. `:` for dialects writing `name : Javid`. Keys
+    /// containing it are delimited by quotation marks. Values don't need to be, as the reader
+    /// splits a line at the first token outside of quotation marks. Defaults to `=`.
+    pub assignment_token: Cow<'static, str>,
+    /// Whether values containing a `#` are delimited by quotation marks, for files read with the
+    /// `inline_comments` read option, which would otherwise cut them off. Defaults to `false`.
+    pub inline_comments: bool,
+    /// Whether values containing a line break are delimited by quotation marks, for files read
+    /// with the `multiline_values` read option, which continues such values on the following
+    /// lines. Defaults to `false`.
+    pub multiline_values: bool,
+}
+
+impl WriteOptions {
+    /// Creates the default options, see the documentation of the individual fields.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sort_keys: false,
+            align_equals: false,
+            digit_grouping: None,
+            space_after_separator: true,
+            trailing_newline: true,
+            crlf: false,
+            null_token: None,
+            real_precision: None,
+            byte_order_mark: false,
+            assignment_token: Cow::Borrowed("="),
+            inline_comments: false,
+            multiline_values: false,
+        }
+    }
+}
+
+impl Default for WriteOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Writer<'a> {
     #[must_use]
     pub const fn new(data_file: &'a Datafile) -> Self {
+        Self::with_options(data_file, WriteOptions::new())
+    }
+
+    /// Creates a new writer for a datafile, formatting it with the given options.
+    #[must_use]
+    pub const fn with_options(data_file: &'a Datafile, options: WriteOptions) -> Self {
         Self {
             data_file,
+            options,
             buffer: String::new(),
+            truncation: None,
         }
     }
 
@@ -39,17 +217,207 @@ impl<'a> Writer<'a> {
     /// # Errors
     ///
     /// This function will return an error if the file cannot be written to.
-    pub fn write(&mut self, path: &str) -> std::io::Result<()> {
+    pub fn write<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        self.write_into(File::create(path)?)
+    }
+
+    /// Writes the datafile to the given output, producing the same output as `write_to_string`.
+    /// Unlike `write_to_string`, the output isn't collected in memory first, but written in
+    /// chunks while the tree is traversed, which keeps memory usage low for big trees. `write`
+    /// uses this as well.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the output cannot be written to.
+    pub fn write_into<W: Write>(&mut self, mut output: W) -> std::io::Result<()> {
+        let mut stream = Stream {
+            output: Some(&mut output),
+            ..Stream::default()
+        };
+
+        self.buffer.clear();
+        self.write_node(self.data_file, 0, &mut stream);
+
+        if !self.options.trailing_newline && self.buffer.ends_with('\n') {
+            self.buffer.pop();
+        }
+
+        stream.write(&self.buffer, &self.options);
+        self.buffer.clear();
+
+        if let Some(error) = stream.error {
+            return Err(error);
+        }
+
+        output.flush()
+    }
+
+    /// Writes a datafile to disk without the risk of corrupting an existing file. The datafile is
+    /// written to a temporary file next to the destination first, which then replaces the
+    /// destination. If writing fails or is interrupted, the existing file is left untouched.
+    ///
+    /// Should the destination be on another device than the temporary file, the temporary file is
+    /// copied over instead, which isn't atomic.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the temporary file cannot be written to, or if it
+    /// cannot be moved to the destination.
+    pub fn write_atomic<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let temp_path = Self::temp_path(path);
+
+        let result =
+            self.write_synced(&temp_path)
+                .and_then(|()| match fs::rename(&temp_path, path) {
+                    Err(error) if Self::is_cross_device(&error) => {
+                        fs::copy(&temp_path, path).map(|_| ())
+                    }
+                    result => result,
+                });
+
+        // The temporary file only remains if something went wrong, or if it had to be copied.
+        let _ = fs::remove_file(&temp_path);
+        result
+    }
+
+    /// Appends a datafile to the end of a file on disk, creating the file if it doesn't exist. The
+    /// datafile is written at the top level of the file, just like `write` would write it. The
+    /// rest of the file is neither read nor validated, so the caller is responsible for the
+    /// combined file to be structurally correct.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be opened or written to.
+    pub fn append<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut output = self.write_to_string();
+
+        // The appended data must start on a line of its own.
+        if file.seek(SeekFrom::End(0))? > 0 {
+            if let Some(stripped) = output.strip_prefix('\u{FEFF}') {
+                output = stripped.to_string();
+            }
+
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+
+            if last[0] != b'\n' {
+                output.insert_str(0, if self.options.crlf { "\r\n" } else { "\n" });
+            }
+        }
+
+        file.write_all(output.as_bytes())
+    }
+
+    /// Writes a single child node of the datafile to disk, including the node itself, so it's
+    /// read back under its name. The rest of the datafile isn't written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no child node with the given name, or if
+    /// the file cannot be written to.
+    pub fn write_subtree<P: AsRef<Path>>(&mut self, name: &str, path: P) -> std::io::Result<()> {
+        let output = self.subtree_to_string(name).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("No node found with name {name}"),
+            )
+        })?;
+
+        let mut file = File::create(path)?;
+        file.write_all(output.as_bytes())
+    }
+
+    /// Writes the datafile to the given path and waits until it has reached the disk.
+    fn write_synced(&mut self, path: &Path) -> std::io::Result<()> {
         let mut file = File::create(path)?;
-        self.write_node(self.data_file, 0);
+        self.write_into(&mut file)?;
+        file.sync_all()
+    }
 
+    /// Returns the path of the temporary file `write_atomic` uses for the given destination.
+    pub(crate) fn temp_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}.tmp", std::process::id()));
+        PathBuf::from(name)
+    }
+
+    /// Returns whether an error was caused by renaming a file across devices.
+    fn is_cross_device(error: &std::io::Error) -> bool {
+        // EXDEV on Unix, ERROR_NOT_SAME_DEVICE on Windows.
+        let code = if cfg!(windows) { 17 } else { 18 };
+        error.raw_os_error() == Some(code)
+    }
+
+    /// Serializes the datafile into a string, exactly as `write` would write it to disk.
+    pub(crate) fn write_to_string(&mut self) -> String {
+        self.buffer.clear();
+        self.write_node(self.data_file, 0, &mut Stream::default());
+        self.finish()
+    }
+
+    /// Serializes a single child node of the datafile into a string, exactly as `write_subtree`
+    /// would write it to disk. Returns `None` if there is no child node with the given name.
+    pub(crate) fn subtree_to_string(&mut self, name: &str) -> Option<String> {
+        let index = *self
+            .data_file
+            .object_map
+            .get(&*self.data_file.map_key(name))?;
+        let (name, node) = &self.data_file.object_vec[index];
+
+        self.buffer.clear();
+        self.write_child(name, node, 0, 0, &mut Stream::default());
+        Some(self.finish())
+    }
+
+    /// Applies the options that concern the output as a whole to the buffer, and returns it.
+    fn finish(&mut self) -> String {
         // Deviation from the original implementation. I just like this better. Removes the leading
         // newline at the top of the file.
         if self.buffer.starts_with('\n') {
             self.buffer.remove(0);
         }
 
-        file.write_all(self.buffer.as_bytes())
+        if !self.options.trailing_newline && self.buffer.ends_with('\n') {
+            self.buffer.pop();
+        }
+
+        if self.options.byte_order_mark {
+            self.buffer.insert(0, '\u{FEFF}');
+        }
+
+        if self.options.crlf {
+            return self.buffer.replace('\n', "\r\n");
+        }
+
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Serializes the datafile for its `Debug` output. Unlike `write_to_string`, the values of the
+    /// datafile itself are written as well, on the first line after an equal sign.
+    pub(crate) fn write_to_debug_string(&mut self, truncation: Option<Truncation>) -> String {
+        self.buffer.clear();
+        self.truncation = truncation;
+
+        if !self.data_file.contents.is_empty() {
+            self.buffer.push_str("= ");
+            self.write_value(self.data_file);
+        }
+
+        self.write_node(self.data_file, 0, &mut Stream::default());
+
+        if self.buffer.starts_with('\n') {
+            self.buffer.remove(0);
+        }
+
+        std::mem::take(&mut self.buffer)
     }
 
     /// Writes a node to the file. Should the node itself contain other nodes, it will recursively
@@ -59,18 +427,107 @@ impl<'a> Writer<'a> {
     ///
     /// * `node` - datafile (node) to write
     /// * `indent` - the number of indentations to write before the node
-    fn write_node(&mut self, datafile: &'a Datafile, indent_level: usize) {
-        for (name, node) in &datafile.object_vec {
-            if node.object_vec.is_empty() {
-                self.write_key(node, name, indent_level);
-                self.write_value(node);
-                continue;
+    fn write_node(&mut self, datafile: &'a Datafile, indent_level: usize, stream: &mut Stream<'_>) {
+        let key_width = self.key_width(datafile);
+        let children = self.ordered_children(datafile);
+        let shown = self.truncation.map_or(children.len(), |truncation| {
+            truncation.max_entries.min(children.len())
+        });
+
+        for (name, node) in children.iter().take(shown).copied() {
+            self.write_child(name, node, indent_level, key_width, stream);
+            stream.flush(&mut self.buffer, &self.options);
+        }
+
+        if shown < children.len() {
+            let count = children[shown..]
+                .iter()
+                .filter(|(_, node)| !node.is_comment)
+                .map(|(_, node)| 1 + node.total_node_count())
+                .sum();
+
+            self.write_omitted(count, indent_level);
+        }
+    }
+
+    /// Writes a single child node, either as a line of values or as a block if it has children of
+    /// its own.
+    fn write_child(
+        &mut self,
+        name: &str,
+        node: &'a Datafile,
+        indent_level: usize,
+        key_width: usize,
+        stream: &mut Stream<'_>,
+    ) {
+        if node.object_vec.is_empty() {
+            self.write_doc_comment(node, indent_level);
+            self.write_key(node, name, indent_level, key_width);
+            self.write_value(node);
+            return;
+        }
+
+        self.write_node_header(node, indent_level, name);
+
+        match self.truncation {
+            Some(truncation) if indent_level + 1 >= truncation.max_depth => {
+                self.write_omitted(node.total_node_count(), indent_level + 1);
             }
+            _ => self.write_node(node, indent_level + 1, stream),
+        }
+
+        self.write_node_footer(indent_level);
+    }
+
+    /// Writes the line replacing the part of a tree omitted due to truncation.
+    fn write_omitted(&mut self, count: usize, indent_level: usize) {
+        self.buffer.push_str(&format!(
+            "{}... ({count} {})\n",
+            self.get_indentation(indent_level),
+            if count == 1 { "node" } else { "nodes" }
+        ));
+    }
+
+    /// Returns the width keys of values are padded to, if `align_equals` is enabled.
+    fn key_width(&self, datafile: &Datafile) -> usize {
+        if !self.options.align_equals {
+            return 0;
+        }
+
+        datafile
+            .object_vec
+            .iter()
+            .filter(|(_, node)| !node.is_comment && node.object_vec.is_empty())
+            .map(|(name, node)| self.escape_key(name, node.quote_char).chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the children of a node in the order they should be written in.
+    fn ordered_children(&self, datafile: &'a Datafile) -> Vec<&'a (Arc<str>, Datafile)> {
+        if !self.options.sort_keys {
+            return datafile.object_vec.iter().collect();
+        }
 
-            self.write_node_header(indent_level, name);
-            self.write_node(node, indent_level + 1);
-            self.write_node_footer(indent_level);
+        // Comments are grouped with the node following them, so they are moved along with it.
+        // Comments at the end of a node don't precede anything and aren't sorted.
+        let mut groups = vec![];
+        let mut trailing = vec![];
+
+        for entry in &datafile.object_vec {
+            trailing.push(entry);
+
+            if !entry.1.is_comment {
+                groups.push((&*entry.0, std::mem::take(&mut trailing)));
+            }
         }
+
+        groups.sort_by_key(|(name, _)| *name);
+        groups
+            .into_iter()
+            .flat_map(|(_, entries)| entries)
+            .chain(trailing)
+            .collect()
     }
 
     /// Writes a node's key to the buffer. If the node has a value, it will be followed by an
@@ -90,18 +547,45 @@ impl<'a> Writer<'a> {
     /// "#foo"
     /// ```
     #[inline]
-    fn write_key(&mut self, node: &Datafile, name: &str, indent_level: usize) {
+    fn write_key(&mut self, node: &Datafile, name: &str, indent_level: usize, width: usize) {
+        let name = if node.is_comment && name.contains('\n') {
+            // A block of grouped comments has every line indented like the first one.
+            let indentation = format!("\n{}", self.get_indentation(indent_level));
+            Cow::Owned(name.replace('\n', &indentation))
+        } else if node.is_comment {
+            Cow::Borrowed(name)
+        } else {
+            self.escape_key(name, node.quote_char)
+        };
+
+        let assignment = if node.is_comment {
+            String::new()
+        } else {
+            format!(" {} ", self.options.assignment_token)
+        };
+
         self.buffer.push_str(&format!(
-            "{}{name}{}",
+            "{}{name:width$}{assignment}",
             self.get_indentation(indent_level),
-            if node.is_comment { "" } else { " = " },
         ));
     }
 
-    /// Takes a node's content and writes it to the buffer in list format.
+    /// Delimits a key by quotation marks if it contains characters that would otherwise be
+    /// interpreted by the reader, i.e. the assignment token, braces or a comment marker.
+    #[inline]
+    fn escape_key<'b>(&self, name: &'b str, quote_char: char) -> Cow<'b, str> {
+        if name.contains(['{', '}', '#']) || name.contains(&*self.options.assignment_token) {
+            Cow::Owned(format!("{quote_char}{name}{quote_char}"))
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
+    /// Takes a node's content and writes it to the buffer in list format, using the node's own
+    /// list separator.
     ///
     /// # Example
-    /// (The following examples assume a list separator of `,` specified in the datafile.)
+    /// (The following examples assume a list separator of `,` specified in the node.)
     ///
     ///
     /// ```no_run
@@ -112,36 +596,131 @@ impl<'a> Writer<'a> {
     /// "foo, bar, baz"
     /// ```
     ///
-    /// If a value contains a list separator, it will be delimited by quotes.
+    /// If a value contains a list separator, starts or ends with whitespace, including tabs, or is
+    /// empty, it will be delimited by quotes.
     /// ```no_run
-    /// let contents = ["foo", "bar, baz"];
+    /// let contents = ["foo", "bar, baz", " padded "];
     /// ```
     ///
     /// Gets written to the buffer as:
     ///
     /// ```text
-    /// ""foo, "bar, baz""
+    /// ""foo, "bar, baz", " padded """
     /// ```
     #[inline]
     fn write_value(&mut self, node: &Datafile) {
-        self.buffer.push_str(
-            &node
-                .contents
-                .iter()
-                .map(|value| {
-                    if value.contains(self.data_file.list_separator) {
-                        format!("\"{}\"", value)
-                    } else {
-                        value.to_string()
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join(&format!("{} ", self.data_file.list_separator)),
-        );
-
+        self.buffer
+            .push_str(&Self::value_string(node, &self.options));
         self.buffer.push('\n');
     }
 
+    /// Formats the values of a node the way `write_value` writes them, without the line break.
+    pub(crate) fn value_string(node: &Datafile, options: &WriteOptions) -> String {
+        if let Some(grouped) = Self::grouped_integer(node, options) {
+            return format!("{quote}{grouped}{quote}", quote = node.quote_char);
+        }
+
+        // A single value needs no joining, so it's formatted directly.
+        if let [value] = node.contents.as_slice() {
+            return Self::format_value(node, 0, value, options).into_owned();
+        }
+
+        node.contents
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Self::format_value(node, index, value, options))
+            .collect::<Vec<_>>()
+            .join(&if options.space_after_separator {
+                format!("{} ", node.list_separator)
+            } else {
+                node.list_separator.to_string()
+            })
+    }
+
+    /// Formats a single value of a node, delimiting it by quotes if the reader would otherwise
+    /// read it differently.
+    fn format_value<'b>(
+        node: &Datafile,
+        index: usize,
+        value: &'b str,
+        options: &'b WriteOptions,
+    ) -> Cow<'b, str> {
+        let null_token = options.null_token.as_deref();
+        if let Some(null_token) = null_token.filter(|_| node.is_null(index)) {
+            return Cow::Borrowed(null_token);
+        }
+
+        let value = Self::rounded_real(node, index, value, options);
+
+        if value.contains(node.list_separator)
+            || null_token == Some(&*value)
+            || (options.inline_comments && value.contains('#'))
+            || (options.multiline_values && value.contains('\n'))
+            || value.is_empty()
+            || value.trim() != value
+        {
+            Cow::Owned(format!("{quote}{value}{quote}", quote = node.quote_char))
+        } else {
+            value
+        }
+    }
+
+    /// Rounds a value to the configured number of decimals, if it was set as a real.
+    fn rounded_real<'b>(
+        node: &Datafile,
+        index: usize,
+        value: &'b str,
+        options: &WriteOptions,
+    ) -> Cow<'b, str> {
+        match (options.real_precision, value.parse::<f32>()) {
+            (Some(precision), Ok(real)) if node.reals.binary_search(&index).is_ok() => {
+                Cow::Owned(format!("{real:.precision$}"))
+            }
+            _ => Cow::Borrowed(value),
+        }
+    }
+
+    /// Returns the value of a node with its digits grouped, if grouping is enabled and the node
+    /// holds a single integer with more than three digits.
+    fn grouped_integer(node: &Datafile, options: &WriteOptions) -> Option<String> {
+        let separator = options
+            .digit_grouping
+            .filter(|char| matches!(char, ',' | '_'))?;
+        let [value] = node.contents.as_slice() else {
+            return None;
+        };
+
+        let digits = value.trim_start_matches(['-', '+']);
+        if value.parse::<i128>().is_err() || digits.len() <= 3 {
+            return None;
+        }
+
+        let mut grouped = value[..value.len() - digits.len()].to_string();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(separator);
+            }
+
+            grouped.push(digit);
+        }
+
+        Some(grouped)
+    }
+
+    /// Writes the doc comment of a node to the buffer, every line directly after a `#`.
+    #[inline]
+    fn write_doc_comment(&mut self, node: &Datafile, indent_level: usize) {
+        let Some(comment) = &node.doc_comment else {
+            return;
+        };
+
+        let indentation = self.get_indentation(indent_level);
+
+        for line in comment.lines() {
+            self.buffer.push_str(&format!("{indentation}#{line}\n"));
+        }
+    }
+
     /// Writes a node's header to the buffer.
     ///
     /// # Example
@@ -152,10 +731,25 @@ impl<'a> Writer<'a> {
     /// {
     /// ```
     #[inline]
-    fn write_node_header(&mut self, indent_level: usize, name: &str) {
-        let indentation = self.get_indentation(indent_level);
+    fn write_node_header(&mut self, node: &Datafile, indent_level: usize, name: &str) {
+        self.buffer.push('\n');
+        self.write_doc_comment(node, indent_level);
+
+        // A node with both values and children has its values written in the header, e.g.
+        // `name = a, b` followed by the block. The reader attaches the block to those values.
+        if node.contents.is_empty() {
+            self.buffer.push_str(&format!(
+                "{}{}\n",
+                self.get_indentation(indent_level),
+                self.escape_key(name, node.quote_char)
+            ));
+        } else {
+            self.write_key(node, name, indent_level, 0);
+            self.write_value(node);
+        }
+
         self.buffer
-            .push_str(&format!("\n{indentation}{}\n{indentation}{{\n", name));
+            .push_str(&format!("{}{{\n", self.get_indentation(indent_level)));
     }
 
     /// Writes a node's footer to the buffer. This is just the closing brace.
@@ -171,3 +765,602 @@ impl<'a> Writer<'a> {
         self.data_file.whitespace_sequence.repeat(indent_level)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::reader::Reader;
+    use crate::test_utils::temp_path;
+
+    fn round_trip(datafile: &Datafile) -> Datafile {
+        let text = Writer::new(datafile).write_to_string();
+        let mut result = Datafile::new(Some(datafile.list_separator), None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.trim_values = false;
+        reader.read_str(&text).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_write_reserved_keys() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("a=b").set_string("value", 0);
+        datafile.get("#c").set_integer(1, 0);
+        datafile.get("{d}").get("e").set_integer(2, 0);
+
+        let text = Writer::new(&datafile).write_to_string();
+        assert_eq!(
+            text,
+            "\"a=b\" = value\n\"#c\" = 1\n\n\"{d}\"\n{\n\te = 2\n}\n"
+        );
+
+        let mut result = round_trip(&datafile);
+        assert_eq!(result.get("a=b").get_string(0), "value");
+        assert_eq!(result.get("#c").get_integer(0), 1);
+        assert_eq!(result.get("{d}").get("e").get_integer(0), 2);
+        datafile.assert_round_trips();
+    }
+
+    #[test]
+    fn test_write_comment_markers() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("a#b").set_string("c#d", 0);
+
+        let text = Writer::new(&datafile).write_to_string();
+        assert_eq!(text, "\"a#b\" = c#d\n");
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.inline_comments = true;
+        let text = writer.write_to_string();
+        assert_eq!(text, "\"a#b\" = \"c#d\"\n");
+
+        let mut result = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.inline_comments = true;
+        reader.read_str(&text).unwrap();
+        assert_eq!(result.get("a#b").get_string(0), "c#d");
+    }
+
+    #[test]
+    fn test_write_real_precision() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("height").set_real(1.88, 0);
+        datafile.get("position").set_real_list(&[0.123_456, -2.5]);
+        datafile.get("count").set_integer(3, 0);
+        datafile.get("version").set_string("v1.2", 0);
+        datafile.get("tag").set_string("1.10", 0);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.real_precision = Some(2);
+        let text = writer.write_to_string();
+        assert_eq!(
+            text,
+            "height = 1.88\nposition = 0.12, -2.50\ncount = 3\nversion = v1.2\ntag = 1.10\n"
+        );
+
+        writer.options.real_precision = Some(0);
+        let text = writer.write_to_string();
+        assert_eq!(
+            text,
+            "height = 2\nposition = 0, -2\ncount = 3\nversion = v1.2\ntag = 1.10\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        Reader::new(&mut result).read_str(&text).unwrap();
+        assert_eq!(result.get("height").get_real(0), 2.0);
+        assert_eq!(result.get("position").get_real_list(), [0.0, -2.0]);
+    }
+
+    #[test]
+    fn test_write_byte_order_mark() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("name").set_string("Javid", 0);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.byte_order_mark = true;
+        assert_eq!(writer.write_to_string(), "\u{FEFF}name = Javid\n");
+
+        let path = temp_path("byte_order_mark.txt");
+        writer.append(&path).unwrap();
+        writer.append(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(text, "\u{FEFF}name = Javid\nname = Javid\n");
+    }
+
+    #[test]
+    fn test_write_assignment_token() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("url").set_string("https://example.com", 0);
+        datafile.get("a:b").set_string("c = d", 0);
+        datafile.get("a=b").set_integer(1, 0);
+        datafile.get("node").set_string("e", 0);
+        datafile
+            .get("node")
+            .get("child")
+            .set_string_list(&["f", "g:h"]);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.assignment_token = ":".into();
+        let text = writer.write_to_string();
+        assert_eq!(
+            text,
+            "url : https://example.com\n\"a:b\" : c = d\na=b : 1\n\nnode : e\n{\n\tchild : f, g:h\n}\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.assignment_token = ":".into();
+        reader.read_str(&text).unwrap();
+        assert_eq!(result, datafile);
+    }
+
+    #[test]
+    fn test_write_into() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.set_doc_comment("header");
+        for index in 0..5_000 {
+            let node = datafile.get(&format!("node_{index}"));
+            node.get("name").set_string("ünïcödé", 0);
+            node.get("child")
+                .get("values")
+                .set_integer_list(&[index, 2, 3]);
+        }
+        datafile.get("last").set_integer(1, 0);
+
+        for (crlf, trailing_newline, byte_order_mark) in [
+            (false, true, false),
+            (true, false, true),
+            (false, false, false),
+        ] {
+            let mut writer = Writer::new(&datafile);
+            writer.options.crlf = crlf;
+            writer.options.trailing_newline = trailing_newline;
+            writer.options.byte_order_mark = byte_order_mark;
+
+            let text = writer.write_to_string();
+            assert!(text.len() > STREAM_CHUNK_SIZE * 4);
+
+            let mut output = vec![];
+            writer.write_into(&mut output).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), text);
+        }
+
+        let mut output = vec![];
+        Writer::new(&Datafile::new(None, None))
+            .write_into(&mut output)
+            .unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_write_digit_grouping() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("population").set_integer(-1_234_567, 0);
+        datafile.get("small").set_integer(100, 0);
+        datafile.get("list").set_integer_list(&[1000, 2000]);
+        datafile.get("name").set_string("1000a", 0);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.digit_grouping = Some(',');
+        let text = writer.write_to_string();
+        assert_eq!(
+            text,
+            "population = \"-1,234,567\"\nsmall = 100\nlist = 1000, 2000\nname = 1000a\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        Reader::new(&mut result).read_str(&text).unwrap();
+        assert_eq!(result.get("population").get_value_count(), 1);
+        assert_eq!(result.get("population").get_integer(0), -1_234_567);
+        assert_eq!(result.get("small").get_integer(0), 100);
+
+        for separator in [',', '_'] {
+            let mut datafile = Datafile::new(None, None);
+            for value in [1_500, 12_345, -123_456, 1_000_000] {
+                datafile.get(&format!("n{value}")).set_integer(value, 0);
+            }
+
+            let mut writer = Writer::new(&datafile);
+            writer.options.digit_grouping = Some(separator);
+            let text = writer.write_to_string();
+            assert!(text.contains(&format!("\"12{separator}345\"")));
+
+            let mut result = Datafile::new(None, None);
+            Reader::new(&mut result).read_str(&text).unwrap();
+            for value in [1_500, 12_345, -123_456, 1_000_000] {
+                assert_eq!(result.get(&format!("n{value}")).get_integer(0), value);
+            }
+        }
+
+        // Separators that wouldn't be read back are ignored.
+        writer.options.digit_grouping = Some('.');
+        assert!(writer
+            .write_to_string()
+            .starts_with("population = -1234567\n"));
+    }
+
+    #[test]
+    fn test_write_multiline_values() {
+        let mut datafile = Datafile::new(None, None);
+        datafile
+            .get("text")
+            .set_string("first line\n  second, indented\n", 0);
+        datafile.get("text").set_string("other", 1);
+        datafile.get("after").set_integer(1, 0);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.multiline_values = true;
+        let text = writer.write_to_string();
+        assert_eq!(
+            text,
+            "text = \"first line\n  second, indented\n\", other\nafter = 1\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.multiline_values = true;
+        reader.options.trim_values = false;
+        reader.options.record_source_lines = true;
+        reader.read_str(&text).unwrap();
+
+        assert_eq!(result, datafile);
+        assert_eq!(result.get("after").source_line(), Some(4));
+    }
+
+    #[test]
+    fn test_write_options() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("zeta").set_integer(1_000_000, 0);
+        datafile.get("list").set_string_list(&["a", "b"]);
+        datafile.get("node").get("x").set_integer(1, 0);
+
+        let options = WriteOptions {
+            sort_keys: true,
+            align_equals: true,
+            digit_grouping: Some('_'),
+            space_after_separator: false,
+            trailing_newline: false,
+            crlf: true,
+            ..WriteOptions::default()
+        };
+        let text = datafile.write_to_string_with(&options);
+        assert_eq!(
+            text,
+            "list = a,b\r\n\r\nnode\r\n{\r\n\tx = 1\r\n}\r\nzeta = \"1_000_000\""
+        );
+
+        let mut result = Datafile::new(None, None);
+        Reader::new(&mut result).read_str(&text).unwrap();
+        assert_eq!(result.get("zeta").get_integer(0), 1_000_000);
+        assert_eq!(result.get("list").get_string_list(), ["a", "b"]);
+        assert_eq!(result.get("node").get("x").get_integer(0), 1);
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            datafile.write_to_string_with(&WriteOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_write_null_values() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("values").set_string("a", 0);
+        datafile.get("values").set_null(1);
+        datafile.get("values").set_string("", 2);
+        datafile.get("values").set_string("~", 3);
+
+        let options = WriteOptions {
+            null_token: Some("~".to_string()),
+            ..WriteOptions::default()
+        };
+        let text = datafile.write_to_string_with(&options);
+        assert_eq!(text, "values = a, ~, \"\", \"~\"\n");
+
+        let mut result = Datafile::new(None, None);
+        let mut reader = Reader::new(&mut result);
+        reader.options.null_token = Some("~".to_string());
+        reader.read_str(&text).unwrap();
+
+        let values = result.get("values");
+        assert_eq!(values.get_string_list(), ["a", "", "", "~"]);
+        assert!(values.is_null(1));
+        assert!(!values.is_null(2));
+        assert!(!values.is_null(3));
+        assert_eq!(result, datafile);
+
+        // Without a null token, null values are written and read as empty ones.
+        let mut result = round_trip(&datafile);
+        assert!(!result.get("values").is_null(1));
+        assert_eq!(result.get("values").get_string(1), "");
+    }
+
+    #[test]
+    fn test_write_empty_list_values() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("middle").set_string("a", 0);
+        datafile.get("middle").set_string("c", 2);
+        datafile.get("trailing").set_string("a", 0);
+        datafile.get("trailing").set_string("b", 1);
+        datafile.get("trailing").set_string("", 2);
+
+        let mut result = round_trip(&datafile);
+        assert_eq!(result.get("middle").get_value_count(), 3);
+        assert_eq!(result.get("middle").get_string(1), "");
+        assert_eq!(result.get("trailing").get_value_count(), 3);
+        assert_eq!(result.get("trailing").get_string(2), "");
+        datafile.assert_round_trips();
+    }
+
+    #[test]
+    fn test_write_padded_values() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("padded").set_string("  padded  ", 0);
+        datafile.get("padded").set_string("plain", 1);
+        datafile.get("empty").set_string("", 0);
+
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            "padded = \"  padded  \", plain\nempty = \"\"\n"
+        );
+
+        let mut result = round_trip(&datafile);
+        assert_eq!(result.get("padded").get_string(0), "  padded  ");
+        assert_eq!(result.get("padded").get_string(1), "plain");
+        assert_eq!(result.get("empty").get_value_count(), 1);
+        datafile.assert_round_trips();
+    }
+
+    #[test]
+    fn test_write_single_values() {
+        let options = WriteOptions {
+            null_token: Some("~".to_string()),
+            real_precision: Some(2),
+            ..WriteOptions::default()
+        };
+
+        for value in [
+            "24", "1.888", "Javid", "a, b", " padded ", "", "~", "# tag", "a\nb",
+        ] {
+            let mut single = Datafile::new(None, None);
+            single.set_string(value, 0);
+
+            // A single value is written exactly like the first one of a list.
+            let mut list = single.clone();
+            list.set_string("x", 1);
+            let expected = Writer::value_string(&list, &options);
+            let expected = expected.strip_suffix(", x").unwrap();
+
+            assert_eq!(
+                Writer::value_string(&single, &options),
+                expected,
+                "{value:?}"
+            );
+        }
+
+        let mut null = Datafile::new(None, None);
+        null.set_null(0);
+        assert_eq!(Writer::value_string(&null, &options), "~");
+        assert_eq!(
+            Writer::value_string(&null, &WriteOptions::default()),
+            "\"\""
+        );
+    }
+
+    #[test]
+    fn test_write_tab_values() {
+        let mut datafile = Datafile::new(None, Some("\t"));
+        let table = datafile.get("table");
+        table.get("row").set_string("\tcol1\tcol2\t", 0);
+        table.get("row").set_string("a\tb", 1);
+
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            "table\n{\n\trow = \"\tcol1\tcol2\t\", a\tb\n}\n"
+        );
+
+        let mut result = round_trip(&datafile);
+        let row = result.get("table").get("row");
+        assert_eq!(row.get_string(0), "\tcol1\tcol2\t");
+        assert_eq!(row.get_string(1), "a\tb");
+        datafile.assert_round_trips();
+    }
+
+    #[test]
+    fn test_write_values_and_children() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("before").set_integer(1, 0);
+        datafile.get("mixed").set_string_list(&["a", "b"]);
+        datafile.get("mixed").get("child").set_integer(2, 0);
+        datafile.get("mixed").get("nested").set_integer(3, 0);
+        datafile
+            .get("mixed")
+            .get("nested")
+            .get("leaf")
+            .set_integer(4, 0);
+        datafile.get("after").set_integer(5, 0);
+
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            "before = 1\n\nmixed = a, b\n{\n\tchild = 2\n\n\tnested = 3\n\t{\n\t\tleaf = 4\n\t}\n}\nafter = 5\n"
+        );
+
+        let mut result = round_trip(&datafile);
+        assert_eq!(result.get_all("mixed").len(), 1);
+        assert_eq!(result.get("mixed").get_string_list(), ["a", "b"]);
+        assert_eq!(result.get_property("mixed.child").get_integer(0), 2);
+        assert_eq!(result.get_property("mixed.nested").get_integer(0), 3);
+        assert_eq!(result.get_property("mixed.nested.leaf").get_integer(0), 4);
+        assert_eq!(result.get("before").get_integer(0), 1);
+        assert_eq!(result.get("after").get_integer(0), 5);
+        assert_eq!(result.total_node_count(), datafile.total_node_count());
+    }
+
+    #[test]
+    fn test_write_doc_comments() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("name").set_string("Javid", 0);
+        datafile.get("name").set_doc_comment(" The name");
+        datafile.get("pc").get("ram").set_integer(32, 0);
+        datafile
+            .get("pc")
+            .set_doc_comment(" First line\n\n Third line");
+        datafile
+            .get("pc")
+            .get("ram")
+            .set_doc_comment("In gigabytes");
+
+        let text = Writer::new(&datafile).write_to_string();
+        assert_eq!(
+            text,
+            "# The name\nname = Javid\n\n# First line\n#\n# Third line\npc\n{\n\t#In gigabytes\n\tram = 32\n}\n"
+        );
+
+        let mut result = round_trip(&datafile);
+        assert_eq!(result.get("name").doc_comment(), Some(" The name"));
+        assert_eq!(
+            result.get("pc").doc_comment(),
+            Some(" First line\n\n Third line")
+        );
+        assert_eq!(
+            result.get_property("pc.ram").doc_comment(),
+            Some("In gigabytes")
+        );
+        assert!(result.object_vec.iter().all(|(_, node)| !node.is_comment));
+        assert_eq!(Writer::new(&result).write_to_string(), text);
+    }
+
+    #[test]
+    fn test_write_aligned_equals() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("name").set_string("Javid", 0);
+        datafile.get("a").set_integer(1, 0);
+        datafile.get("a=b").set_integer(2, 0);
+        datafile.get("node").get("x").set_integer(3, 0);
+        datafile.get("node").get("longer").set_integer(4, 0);
+
+        let mut writer = Writer::new(&datafile);
+        writer.options.align_equals = true;
+        assert_eq!(
+            writer.write_to_string(),
+            "name  = Javid\na     = 1\n\"a=b\" = 2\n\nnode\n{\n\tx      = 3\n\tlonger = 4\n}\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        Reader::new(&mut result)
+            .read_str(&writer.write_to_string())
+            .unwrap();
+        assert_eq!(result.get("a").get_integer(0), 1);
+        assert_eq!(result.get_property("node.longer").get_integer(0), 4);
+    }
+
+    #[test]
+    fn test_write_quote_char() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.quote_char = '\'';
+        datafile.get("json").set_string("{\"a\": 1, \"b\": 2}", 0);
+        datafile.get("json").set_string("plain", 1);
+        datafile.get("a=b").get("c").set_integer(1, 0);
+
+        let text = Writer::new(&datafile).write_to_string();
+        assert_eq!(
+            text,
+            "json = '{\"a\": 1, \"b\": 2}', plain\n\n'a=b'\n{\n\tc = 1\n}\n"
+        );
+
+        let mut result = Datafile::new(None, None);
+        result.quote_char = '\'';
+        Reader::new(&mut result).read_str(&text).unwrap();
+        assert_eq!(result.get("json").get_string(0), "{\"a\": 1, \"b\": 2}");
+        assert_eq!(result.get("json").get_string(1), "plain");
+        assert_eq!(result.get_property("a=b.c").get_integer(0), 1);
+        assert_eq!(result.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_write_sorted_keys() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("b").set_string_list(&["z", "y"]);
+        datafile.push_object(
+            "# about a",
+            Datafile {
+                is_comment: true,
+                ..Datafile::new(None, None)
+            },
+        );
+        datafile.get("a").get("d").set_integer(1, 0);
+        datafile.get("a").get("c").set_integer(2, 0);
+
+        let mut writer = Writer::new(&datafile);
+        assert_eq!(
+            writer.write_to_string(),
+            "b = z, y\n# about a\n\na\n{\n\td = 1\n\tc = 2\n}\n"
+        );
+
+        writer.options.sort_keys = true;
+        assert_eq!(
+            writer.write_to_string(),
+            "# about a\n\na\n{\n\tc = 2\n\td = 1\n}\nb = z, y\n"
+        );
+    }
+
+    #[test]
+    fn test_write_node_separator() {
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("root").set_string_list(&["a", "b;c"]);
+
+        let nested = datafile.get("node").get("nested");
+        nested.list_separator = ';';
+        nested.set_string_list(&["a", "b;c", "d,e"]);
+
+        assert_eq!(
+            Writer::new(&datafile).write_to_string(),
+            "root = a, b;c\n\nnode\n{\n\tnested = a; \"b;c\"; d,e\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_append() {
+        let path = temp_path("append.txt");
+        std::fs::write(&path, "first = 1").unwrap();
+
+        let mut entry = Datafile::new(None, None);
+        entry.get("entry").get("message").set_string("started", 0);
+        Writer::new(&entry).append(&path).unwrap();
+
+        let mut entry = Datafile::new(None, None);
+        entry.get("last").set_integer(2, 0);
+        Writer::new(&entry).append(&path).unwrap();
+
+        let mut datafile = Datafile::new(None, None);
+        datafile.read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(datafile.get("first").get_integer(0), 1);
+        assert_eq!(
+            datafile.get_property("entry.message").get_string(0),
+            "started"
+        );
+        assert_eq!(datafile.get("last").get_integer(0), 2);
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let path = temp_path("atomic.txt");
+        std::fs::write(&path, "first = 1\n").unwrap();
+
+        let mut datafile = Datafile::new(None, None);
+        datafile.get("second").set_integer(2, 0);
+
+        // A directory in place of the temporary file makes writing it fail.
+        let temp_path = Writer::temp_path(&path);
+        std::fs::create_dir(&temp_path).unwrap();
+        assert!(Writer::new(&datafile).write_atomic(&path).is_err());
+        std::fs::remove_dir(&temp_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first = 1\n");
+
+        Writer::new(&datafile).write_atomic(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second = 2\n");
+        assert!(!std::path::Path::new(&temp_path).exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+use std::path::PathBuf;
+
+/// Returns a path in the temporary directory for a test to write a file to, unique to the running
+/// process so concurrent test runs don't interfere.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("olc_datafile_{}_{name}", std::process::id()))
+}